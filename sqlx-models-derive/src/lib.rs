@@ -3,6 +3,7 @@ use convert_case::{Case, Casing};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::__private::TokenStream2;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream, Result};
 use syn::{
     braced, parenthesized, parse_macro_input, parse_str, punctuated::Punctuated, token::Comma,
@@ -12,6 +13,9 @@ use syn::{
 
 mod kw {
     syn::custom_keyword!(table);
+    syn::custom_keyword!(primary_key);
+    syn::custom_keyword!(timestamps);
+    syn::custom_keyword!(soft_delete);
     syn::custom_keyword!(state);
     syn::custom_keyword!(queries);
     syn::custom_keyword!(has_many);
@@ -102,7 +106,11 @@ impl Parse for ModelConfig {
 #[derive(Debug)]
 struct ModelHints {
     ty: Ident,
+    column: Option<String>,
     default: bool,
+    auto_now: bool,
+    auto_now_add: bool,
+    lock_version: bool,
     op_ne: bool,
     op_gt: bool,
     op_gte: bool,
@@ -125,7 +133,11 @@ impl Parse for ModelHints {
 
         let mut hints = ModelHints {
             ty,
+            column: None,
             default: false,
+            auto_now: false,
+            auto_now_add: false,
+            lock_version: false,
             op_ne: false,
             op_gt: false,
             op_gte: false,
@@ -152,6 +164,13 @@ impl Parse for ModelHints {
 
             match input.parse::<Ident>()?.to_string().as_str() {
                 "default" => hints.default = true,
+                "auto_now" => hints.auto_now = true,
+                "auto_now_add" => hints.auto_now_add = true,
+                "lock_version" => hints.lock_version = true,
+                "column" => {
+                    input.parse::<Token![=]>()?;
+                    hints.column = Some(input.parse::<LitStr>()?.value());
+                }
                 "all_ops" => {
                     hints.op_ne = true;
                     hints.op_gt = true;
@@ -192,14 +211,72 @@ impl Parse for ModelHints {
     }
 }
 
+fn field_hints(field: &Field) -> Option<ModelHints> {
+    field
+        .attrs
+        .iter()
+        .find(|a| a.path == parse_str("sqlx_model_hints").unwrap())
+        .map(|found| {
+            found
+                .parse_args::<ModelHints>()
+                .unwrap_or_else(|_| panic!("Arguments for sqlx_model_hints {:?}", found))
+        })
+}
+
+fn is_option(field: &Field) -> bool {
+    is_option_type(&field.ty)
+}
+
+fn is_option_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(TypePath { path, .. }) => path
+            .segments
+            .first()
+            .map(|s| s.ident == "Option")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// Table names may be schema qualified, like billing.invoices, and any part
+// that is not a valid Rust identifier can be given as a string literal.
+fn parse_table_name(input: ParseStream) -> Result<Vec<String>> {
+    let mut segments = vec![];
+    loop {
+        let segment = if input.peek(LitStr) {
+            input.parse::<LitStr>()?.value()
+        } else {
+            input.parse::<Ident>()?.unraw().to_string()
+        };
+        segments.push(quote_identifier(&segment));
+
+        if input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+        } else {
+            break;
+        }
+    }
+    Ok(segments)
+}
+
 #[derive(Debug)]
 struct SqlxModelConf {
     id_type: Type,
+    primary_key: Vec<Ident>,
+    auto_now: Vec<Ident>,
+    auto_now_add: Vec<Ident>,
+    lock_version: Option<Ident>,
     struct_name: Ident,
     extra_struct_attributes: Vec<Attribute>,
     attrs_struct: Ident,
     state_name: Ident,
-    table_name: Ident,
+    table_name: String,
+    table_alias: String,
+    soft_delete: Option<Ident>,
     fields: Punctuated<Field, Comma>,
     queries: Punctuated<Query, Comma>,
     has_many: Punctuated<Association, Comma>,
@@ -207,6 +284,7 @@ struct SqlxModelConf {
     hub_struct: Ident,
     sql_select_columns: String,
     field_idents: Vec<Ident>,
+    columns: Vec<String>,
     hub_builder_method: Ident,
     no_update: bool,
     no_insert: bool,
@@ -221,32 +299,53 @@ impl Parse for SqlxModelConf {
         input.parse::<Token![,]>()?;
         let _ = input.parse::<kw::table>()?;
         input.parse::<Token![:]>()?;
-        let table_name: Ident = input.parse()?;
+        let table_segments = parse_table_name(input)?;
+        let table_name = table_segments.join(".");
+        let table_alias = table_segments.last().unwrap().clone();
         input.parse::<Token![,]>()?;
 
-        let no_update = if input.peek(kw::no_update) {
-            input.parse::<kw::no_update>()?;
-            input.parse::<Token![,]>()?;
-            true
-        } else {
-            false
-        };
-
-        let no_insert = if input.peek(kw::no_insert) {
-            input.parse::<kw::no_insert>()?;
-            input.parse::<Token![,]>()?;
-            true
-        } else {
-            false
-        };
-
-        let no_delete = if input.peek(kw::no_delete) {
-            input.parse::<kw::no_delete>()?;
+        let mut primary_key: Vec<Ident> = vec![format_ident!("id")];
+        let mut timestamps = false;
+        let mut soft_delete: Option<Ident> = None;
+        let mut no_update = false;
+        let mut no_insert = false;
+        let mut no_delete = false;
+
+        loop {
+            if input.peek(kw::primary_key) {
+                input.parse::<kw::primary_key>()?;
+                input.parse::<Token![:]>()?;
+                primary_key = if input.peek(syn::token::Paren) {
+                    let content;
+                    parenthesized!(content in input);
+                    content
+                        .parse_terminated::<Ident, Token![,]>(Ident::parse)?
+                        .into_iter()
+                        .collect()
+                } else {
+                    vec![input.parse()?]
+                };
+            } else if input.peek(kw::soft_delete) {
+                input.parse::<kw::soft_delete>()?;
+                input.parse::<Token![:]>()?;
+                soft_delete = Some(input.parse()?);
+            } else if input.peek(kw::timestamps) {
+                input.parse::<kw::timestamps>()?;
+                timestamps = true;
+            } else if input.peek(kw::no_update) {
+                input.parse::<kw::no_update>()?;
+                no_update = true;
+            } else if input.peek(kw::no_insert) {
+                input.parse::<kw::no_insert>()?;
+                no_insert = true;
+            } else if input.peek(kw::no_delete) {
+                input.parse::<kw::no_delete>()?;
+                no_delete = true;
+            } else {
+                break;
+            }
             input.parse::<Token![,]>()?;
-            true
-        } else {
-            false
-        };
+        }
 
         let whole_struct: ItemStruct = input.parse()?;
 
@@ -279,28 +378,86 @@ impl Parse for SqlxModelConf {
         let fields = named_fields.named;
 
         let hub_struct = format_ident!("{}Hub", struct_name);
-        let sql_select_columns = fields
+
+        let field_idents: Vec<Ident> = fields
+            .clone()
+            .into_iter()
+            .map(|i| i.ident.unwrap())
+            .collect();
+
+        let columns: Vec<String> = fields
             .iter()
             .map(|f| {
+                let column = field_hints(f)
+                    .and_then(|h| h.column)
+                    .unwrap_or_else(|| f.ident.as_ref().unwrap().unraw().to_string());
+                quote_identifier(&column)
+            })
+            .collect();
+
+        let sql_select_columns = fields
+            .iter()
+            .zip(columns.iter())
+            .map(|(f, column)| {
                 let name = f.ident.as_ref().unwrap();
                 let ty = &f.ty;
-                format!(r#"{} as "{}!: {}""#, name, name, quote! { #ty })
+                format!(r#"{} as "{}!: {}""#, column, name, quote! { #ty })
             })
             .collect::<Vec<String>>()
             .join(", \n");
 
-        let field_idents: Vec<Ident> = fields
-            .clone()
-            .into_iter()
-            .map(|i| i.ident.unwrap())
-            .collect();
+        if primary_key.is_empty() {
+            panic!("Primary key needs at least one column");
+        }
 
-        let id_type = fields
+        let primary_key_types: Vec<Type> = primary_key
             .iter()
-            .find(|i| i.ident.as_ref().unwrap() == "id")
-            .expect("struct to have an id field")
-            .ty
-            .clone();
+            .map(|k| {
+                fields
+                    .iter()
+                    .find(|i| i.ident.as_ref().unwrap() == k)
+                    .unwrap_or_else(|| {
+                        panic!("Primary key column {:?} is not a field", k.to_string())
+                    })
+                    .ty
+                    .clone()
+            })
+            .collect();
+
+        let id_type: Type = if primary_key_types.len() == 1 {
+            primary_key_types[0].clone()
+        } else {
+            syn::parse_quote! { (#(#primary_key_types),*) }
+        };
+
+        let mut auto_now_add: Vec<Ident> = vec![];
+        let mut auto_now: Vec<Ident> = vec![];
+        let mut lock_version: Option<Ident> = None;
+        for field in fields.iter() {
+            let ident = field.ident.as_ref().unwrap();
+            let hints = field_hints(field);
+            if hints.as_ref().map(|h| h.lock_version).unwrap_or(false) {
+                lock_version = Some(ident.clone());
+            }
+            let is_timestamp = |name: &str| timestamps && ident == name;
+            if is_timestamp("updated_at") || hints.as_ref().map(|h| h.auto_now).unwrap_or(false) {
+                auto_now.push(ident.clone());
+            } else if is_timestamp("created_at")
+                || hints.as_ref().map(|h| h.auto_now_add).unwrap_or(false)
+            {
+                auto_now_add.push(ident.clone());
+            }
+        }
+
+        if let Some(column) = soft_delete.as_ref() {
+            if !fields.iter().any(|f| f.ident.as_ref() == Some(column)) {
+                panic!("Soft delete column {:?} is not a field", column.to_string());
+            }
+        }
+
+        if timestamps && (auto_now_add.is_empty() || auto_now.is_empty()) {
+            panic!("Models with timestamps need created_at and updated_at fields");
+        }
 
         let hub_builder_method = Ident::new(
             &struct_name.to_string().to_case(Case::Snake),
@@ -309,11 +466,17 @@ impl Parse for SqlxModelConf {
 
         Ok(SqlxModelConf {
             id_type,
+            primary_key,
+            auto_now,
+            auto_now_add,
+            lock_version,
             extra_struct_attributes,
             state_name,
             struct_name,
             attrs_struct,
             table_name,
+            table_alias,
+            soft_delete,
             fields,
             queries,
             has_many,
@@ -321,6 +484,7 @@ impl Parse for SqlxModelConf {
             hub_struct,
             sql_select_columns,
             field_idents,
+            columns,
             hub_builder_method,
             no_update,
             no_insert,
@@ -329,12 +493,118 @@ impl Parse for SqlxModelConf {
     }
 }
 
+impl SqlxModelConf {
+    // Turns the sqlx::Result of a query on this model into a sqlx_models_orm::Result.
+    fn map_err(&self) -> TokenStream2 {
+        let name = LitStr::new(&self.struct_name.to_string(), self.struct_name.span());
+        quote! { .map_err(|e| sqlx_models_orm::Error::from_sqlx(#name, e)) }
+    }
+
+    // Like map_err, for queries that compare against the values of the select's cursor.
+    fn map_cursor_err(&self, select: TokenStream2) -> TokenStream2 {
+        let name = LitStr::new(&self.struct_name.to_string(), self.struct_name.span());
+        quote! { .map_err(|e| sqlx_models_orm::Error::from_sqlx_with_cursor(#name, e, #select.cursor())) }
+    }
+
+    fn column(&self, field: &Ident) -> &str {
+        let position = self
+            .field_idents
+            .iter()
+            .position(|f| f == field)
+            .unwrap_or_else(|| panic!("Column {:?} is not a field", field.to_string()));
+        &self.columns[position]
+    }
+
+    // Fields managed by the database or by delete() and restore(). Timestamps among
+    // them can still be given explicitly, see now_unless().
+    fn is_automatic(&self, field: &Ident) -> bool {
+        self.auto_now.contains(field)
+            || self.auto_now_add.contains(field)
+            || self.soft_delete.as_ref() == Some(field)
+            || self.lock_version.as_ref() == Some(field)
+    }
+
+    // What custom queries select from, excluding soft deleted rows.
+    fn table_for_queries(&self) -> String {
+        match self.soft_delete.as_ref() {
+            Some(column) => format!(
+                "(SELECT * FROM {} WHERE {} IS NULL) AS {}",
+                self.table_name,
+                self.column(column),
+                self.table_alias
+            ),
+            None => self.table_name.clone(),
+        }
+    }
+
+    // The column name of a field as it is in the database, unquoted.
+    fn column_name(&self, field: &Ident) -> String {
+        let column = self.column(field);
+        column[1..column.len() - 1].replace("\"\"", "\"")
+    }
+
+    fn db_type(&self, field: &Ident) -> Option<String> {
+        self.fields
+            .iter()
+            .find(|f| f.ident.as_ref() == Some(field))
+            .and_then(field_hints)
+            .map(|h| h.ty.to_string())
+    }
+
+    // Sets an auto_now or auto_now_add column to 'value', or to now() when it's NULL.
+    fn now_unless(&self, field: &Ident, value: &str) -> String {
+        match self.db_type(field) {
+            Some(db_type) => format!("COALESCE({}::{}, now())", value, db_type),
+            None => format!("COALESCE({}, now())", value),
+        }
+    }
+
+    fn has_composite_primary_key(&self) -> bool {
+        self.primary_key.len() > 1
+    }
+
+    // An expression building an owned Id out of the attributes found in 'source'.
+    fn primary_key_value(&self, source: TokenStream2) -> TokenStream2 {
+        let columns = &self.primary_key;
+        if self.has_composite_primary_key() {
+            quote! { (#(#source.#columns.clone()),*) }
+        } else {
+            quote! { #(#source.#columns.clone())* }
+        }
+    }
+
+    // The primary key WHERE clause, with positional arguments starting at $1.
+    fn primary_key_where_clause(&self) -> String {
+        self.primary_key
+            .iter()
+            .enumerate()
+            .map(|(n, c)| format!("{} = ${}", self.column(c), n + 1))
+            .collect::<Vec<String>>()
+            .join(" AND ")
+    }
+
+    // The variant naming a field in the OrderBy and Column enums.
+    fn column_variant(&self, field: &Ident) -> Ident {
+        Ident::new(&field.to_string().to_case(Case::UpperCamel), field.span())
+    }
+
+    // Columns aliased to field names, for queries decoded with the attrs FromRow.
+    fn runtime_select_columns(&self) -> String {
+        self.field_idents
+            .iter()
+            .map(|f| format!(r#"{} AS "{}""#, self.column(f), f))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
 #[proc_macro]
 pub fn model(tokens: TokenStream) -> TokenStream {
     let conf = parse_macro_input!(tokens as SqlxModelConf);
     let state_name = &conf.state_name;
     let hub_struct = &conf.hub_struct;
     let hub_builder_method = &conf.hub_builder_method;
+    let map_err = conf.map_err();
 
     let base_section = build_base(&conf);
     let select_section = build_select(&conf);
@@ -371,13 +641,13 @@ pub fn model(tokens: TokenStream) -> TokenStream {
           Self{ state }
         }
 
-        pub async fn transactional(mut self) -> sqlx::Result<Self> {
-          self.state.db = self.state.db.transaction().await?;
+        pub async fn transactional(mut self) -> sqlx_models_orm::Result<Self> {
+          self.state.db = self.state.db.transaction().await #map_err?;
           Ok(self)
         }
 
-        pub async fn commit(&self) -> sqlx::Result<()> {
-          self.state.db.commit().await?;
+        pub async fn commit(&self) -> sqlx_models_orm::Result<()> {
+          self.state.db.commit().await #map_err?;
           Ok(())
         }
       }
@@ -410,8 +680,18 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
     let select_struct = format_ident!("Select{}Hub", &struct_name);
     let select_attrs_struct = format_ident!("Select{}", &struct_name);
     let model_order_by = format_ident!("{}OrderBy", &struct_name);
+    let model_column = format_ident!("{}Column", &struct_name);
+    let model_value = format_ident!("{}Value", &struct_name);
     let struct_name_as_string = LitStr::new(&struct_name.to_string(), struct_name.span());
     let field_types: Vec<Type> = conf.fields.clone().into_iter().map(|i| i.ty).collect();
+    let column_variants: Vec<Ident> = field_idents
+        .iter()
+        .map(|i| conf.column_variant(i))
+        .collect();
+    let column_names: Vec<LitStr> = field_idents
+        .iter()
+        .map(|i| LitStr::new(conf.column(i), i.span()))
+        .collect();
 
     let mut belongs_to_structs: Vec<Ident> = vec![];
     let mut belongs_to_builders: Vec<Ident> = vec![];
@@ -419,6 +699,10 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
     let mut maybe_belongs_to_structs: Vec<Ident> = vec![];
     let mut maybe_belongs_to_builders: Vec<Ident> = vec![];
     let mut maybe_belongs_to_columns: Vec<Ident> = vec![];
+    let mut belongs_to_preloaded: Vec<Ident> = vec![];
+    let mut belongs_to_loaders: Vec<Ident> = vec![];
+    let mut maybe_belongs_to_preloaded: Vec<Ident> = vec![];
+    let mut maybe_belongs_to_loaders: Vec<Ident> = vec![];
 
     for c in &conf.belongs_to {
         let field = conf
@@ -448,10 +732,14 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
         );
 
         if is_option {
+            maybe_belongs_to_preloaded.push(format_ident!("preloaded_{}", builder));
+            maybe_belongs_to_loaders.push(format_ident!("load_{}", builder));
             maybe_belongs_to_structs.push(c.model_name.clone());
             maybe_belongs_to_columns.push(c.column_name.clone());
             maybe_belongs_to_builders.push(builder);
         } else {
+            belongs_to_preloaded.push(format_ident!("preloaded_{}", builder));
+            belongs_to_loaders.push(format_ident!("load_{}", builder));
             belongs_to_structs.push(c.model_name.clone());
             belongs_to_columns.push(c.column_name.clone());
             belongs_to_builders.push(builder);
@@ -464,6 +752,15 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
     let mut has_many_scope_methods: Vec<Ident> = vec![];
     let mut has_many_select_structs: Vec<Ident> = vec![];
     let mut has_many_columns: Vec<Ident> = vec![];
+    let mut has_many_foreign_keys: Vec<Ident> = vec![];
+    let mut has_many_column_variants: Vec<TokenStream2> = vec![];
+    let mut has_many_preloaded: Vec<Ident> = vec![];
+    let mut has_many_loaders: Vec<Ident> = vec![];
+
+    if !conf.has_many.is_empty() && conf.has_composite_primary_key() {
+        panic!("has_many associations are not supported on models with a composite primary key");
+    }
+    let primary_key_column = &conf.primary_key[0];
 
     for c in &conf.has_many {
         let builder = Ident::new(
@@ -475,6 +772,12 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
         has_many_select_structs.push(format_ident!("Select{}Hub", c.model_name));
         has_many_structs.push(c.model_name.clone());
         has_many_columns.push(format_ident!("{}_eq", c.column_name));
+        has_many_foreign_keys.push(c.column_name.clone());
+        let child_column = format_ident!("{}Column", c.model_name);
+        let child_variant = conf.column_variant(&c.column_name);
+        has_many_column_variants.push(quote! { #child_column::#child_variant });
+        has_many_preloaded.push(format_ident!("preloaded_{}", builder));
+        has_many_loaders.push(format_ident!("load_{}", builder));
         has_many_builders.push(builder.clone());
     }
 
@@ -491,11 +794,80 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
         })
         .collect();
 
+    let primary_key_value = conf.primary_key_value(quote! { self.attrs });
+
+    let primary_key_type = conf
+        .fields
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(primary_key_column))
+        .map(|f| f.ty.clone())
+        .unwrap();
+
     quote! {
       impl #hub_struct {
         fn init(&self, attrs: #attrs_struct) -> #struct_name {
           #struct_name::new(self.state.clone(), attrs)
         }
+
+        #(
+          /// Loads the associated row of each of 'items' in one query.
+          pub async fn #belongs_to_loaders(&self, items: &mut [#struct_name]) -> sqlx_models_orm::Result<()> {
+            if items.is_empty() {
+              return Ok(());
+            }
+            let ids: Vec<_> = items.iter().map(|i| i.attrs.#belongs_to_columns.clone()).collect();
+            let found: std::collections::HashMap<_, _> = self.state.#belongs_to_builders().find_many(ids).await?
+              .into_iter()
+              .map(|f| (f.primary_key(), f))
+              .collect();
+            for item in items.iter_mut() {
+              item.#belongs_to_preloaded = found.get(&item.attrs.#belongs_to_columns).map(|f| Box::new(f.clone()));
+            }
+            Ok(())
+          }
+        )*
+
+        #(
+          /// Loads the associated row, if any, of each of 'items' in one query.
+          pub async fn #maybe_belongs_to_loaders(&self, items: &mut [#struct_name]) -> sqlx_models_orm::Result<()> {
+            let ids: Vec<_> = items.iter().filter_map(|i| i.attrs.#maybe_belongs_to_columns.clone()).collect();
+            let found: std::collections::HashMap<_, _> = if ids.is_empty() {
+              Default::default()
+            } else {
+              self.state.#maybe_belongs_to_builders().find_many(ids).await?
+                .into_iter()
+                .map(|f| (f.primary_key(), f))
+                .collect()
+            };
+            for item in items.iter_mut() {
+              item.#maybe_belongs_to_preloaded = match &item.attrs.#maybe_belongs_to_columns {
+                None => Some(None),
+                Some(id) => found.get(id).map(|f| Some(Box::new(f.clone()))),
+              };
+            }
+            Ok(())
+          }
+        )*
+
+        #(
+          /// Loads the associated rows of each of 'items' in one query.
+          pub async fn #has_many_loaders(&self, items: &mut [#struct_name]) -> sqlx_models_orm::Result<()> {
+            if items.is_empty() {
+              return Ok(());
+            }
+            let ids: Vec<_> = items.iter().map(|i| i.attrs.#primary_key_column.clone()).collect();
+            let mut found: std::collections::HashMap<#primary_key_type, Vec<#has_many_structs>> = Default::default();
+            for child in self.state.#has_many_builders().find_all_where_any(#has_many_column_variants, ids).await? {
+              if let Some(key) = <_ as sqlx_models_orm::ForeignKey<#primary_key_type>>::key(&child.attrs.#has_many_foreign_keys) {
+                found.entry(key.clone()).or_default().push(child);
+              }
+            }
+            for item in items.iter_mut() {
+              item.#has_many_preloaded = Some(found.get(&item.attrs.#primary_key_column).cloned().unwrap_or_default());
+            }
+            Ok(())
+          }
+        )*
       }
 
       #[derive(Clone, serde::Serialize)]
@@ -504,20 +876,45 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
         pub state: #state_name,
         #[serde(flatten)]
         pub attrs: #attrs_struct,
+        #(
+          #[serde(skip)]
+          pub #belongs_to_preloaded: Option<Box<#belongs_to_structs>>,
+        )*
+        #(
+          #[serde(skip)]
+          pub #maybe_belongs_to_preloaded: Option<Option<Box<#maybe_belongs_to_structs>>>,
+        )*
+        #(
+          #[serde(skip)]
+          pub #has_many_preloaded: Option<Vec<#has_many_structs>>,
+        )*
       }
 
       impl #struct_name {
         pub fn new(state: #state_name, attrs: #attrs_struct) -> Self {
-          Self{ state, attrs }
+          Self {
+            state,
+            attrs,
+            #(#belongs_to_preloaded: None,)*
+            #(#maybe_belongs_to_preloaded: None,)*
+            #(#has_many_preloaded: None,)*
+          }
         }
 
-        pub async fn reload(&mut self) -> sqlx::Result<()> {
+        pub async fn reload(&mut self) -> sqlx_models_orm::Result<()> {
           self.attrs = self.reloaded().await?.attrs;
+          #(self.#belongs_to_preloaded = None;)*
+          #(self.#maybe_belongs_to_preloaded = None;)*
+          #(self.#has_many_preloaded = None;)*
           Ok(())
         }
 
-        pub async fn reloaded(&self) -> sqlx::Result<Self> {
-          self.state.#hub_builder_method().find(self.id()).await
+        pub async fn reloaded(&self) -> sqlx_models_orm::Result<Self> {
+          self.state.#hub_builder_method().find(self.primary_key()).await
+        }
+
+        pub fn primary_key(&self) -> #id_type {
+          #primary_key_value
         }
 
         #(
@@ -527,13 +924,19 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
         )*
 
         #(
-          pub async fn #belongs_to_builders(&self) -> sqlx::Result<#belongs_to_structs> {
+          pub async fn #belongs_to_builders(&self) -> sqlx_models_orm::Result<#belongs_to_structs> {
+            if let Some(found) = &self.#belongs_to_preloaded {
+              return Ok((**found).clone());
+            }
             self.state.#belongs_to_builders().find(self.#belongs_to_columns()).await
           }
         )*
 
         #(
-          pub async fn #maybe_belongs_to_builders(&self) -> sqlx::Result<Option<#maybe_belongs_to_structs>> {
+          pub async fn #maybe_belongs_to_builders(&self) -> sqlx_models_orm::Result<Option<#maybe_belongs_to_structs>> {
+            if let Some(found) = &self.#maybe_belongs_to_preloaded {
+              return Ok(found.as_deref().cloned());
+            }
             if let Some(a) = self.#maybe_belongs_to_columns() {
               self.state.#maybe_belongs_to_builders().find(a).await.map(Some)
             } else {
@@ -544,17 +947,58 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
 
         #(
           pub fn #has_many_scope_methods(&self) -> #has_many_select_structs {
-            self.state.#has_many_builders().select().#has_many_columns(self.id())
+            self.state.#has_many_builders().select().#has_many_columns(&self.attrs.#primary_key_column)
           }
         )*
 
         #(
-          pub async fn #has_many_methods(&self) -> sqlx::Result<Vec<#has_many_structs>> {
+          pub async fn #has_many_methods(&self) -> sqlx_models_orm::Result<Vec<#has_many_structs>> {
+            if let Some(found) = &self.#has_many_preloaded {
+              return Ok(found.clone());
+            }
             self.#has_many_scope_methods().all().await
           }
         )*
       }
 
+      #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+      pub enum #model_column {
+        #(#column_variants,)*
+      }
+
+      impl #model_column {
+        /// The column name as used in SQL.
+        pub fn column(&self) -> &'static str {
+          match self {
+            #(#model_column::#column_variants => #column_names,)*
+          }
+        }
+      }
+
+      /// The value of one column, as used when grouping rows.
+      #[derive(Debug, Clone, PartialEq)]
+      pub enum #model_value {
+        #(#column_variants(#field_types),)*
+      }
+
+      impl #model_value {
+        fn from_row(column: #model_column, row: &sqlx::postgres::PgRow, index: usize) -> sqlx::Result<Self> {
+          use sqlx::Row;
+          Ok(match column {
+            #(#model_column::#column_variants => #model_value::#column_variants(row.try_get_unchecked(index)?),)*
+          })
+        }
+      }
+
+      /// The column a value was read from.
+      impl From<&#model_value> for #model_column {
+        fn from(value: &#model_value) -> Self {
+          match value {
+            #(#model_value::#column_variants(_) => #model_column::#column_variants,)*
+          }
+        }
+      }
+
       #[sqlx_models_orm::async_trait]
       impl sqlx_models_orm::SqlxModel for #struct_name {
         type State = #state_name;
@@ -591,6 +1035,9 @@ fn build_base(conf: &SqlxModelConf) -> TokenStream2 {
 }
 
 fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
+    let map_err = conf.map_err();
+    let map_cursor_err = conf.map_cursor_err(quote! { self });
+    let map_select_cursor_err = conf.map_cursor_err(quote! { select });
     let state_name = &conf.state_name;
     let struct_name = &conf.struct_name;
     let hub_struct = &conf.hub_struct;
@@ -599,6 +1046,8 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
     let field_idents = &conf.field_idents;
     let select_struct = format_ident!("Select{}Hub", &struct_name);
     let model_order_by = format_ident!("{}OrderBy", &struct_name);
+    let model_column = format_ident!("{}Column", &struct_name);
+    let model_value = format_ident!("{}Value", &struct_name);
     let select_attrs_struct = format_ident!("Select{}", &struct_name);
     let id_type = &conf.id_type;
     let span = conf.struct_name.span();
@@ -610,10 +1059,16 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
     let mut builder_method_string_idents: Vec<Ident> = vec![];
     let mut where_clauses = vec![];
     let mut args = vec![];
+    let mut condition_pushes = vec![];
+    let mut aggregate_idents: Vec<Ident> = vec![];
+    let mut aggregate_types: Vec<Type> = vec![];
+    let mut aggregate_starts: Vec<LitStr> = vec![];
+    let mut aggregate_by_idents: Vec<Ident> = vec![];
+    let mut aggregate_exprs: Vec<LitStr> = vec![];
 
     let sort_variants: Vec<Ident> = field_idents
         .iter()
-        .map(|i| Ident::new(&i.to_string().to_case(Case::UpperCamel), i.span()))
+        .map(|i| conf.column_variant(i))
         .collect();
 
     for field in conf.fields.clone().into_iter() {
@@ -645,15 +1100,9 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
         };
 
         let ident = &field.ident.as_ref().unwrap();
+        let column = conf.column(ident);
 
-        if let Some(found) = field
-            .attrs
-            .iter()
-            .find(|a| a.path == parse_str("sqlx_model_hints").unwrap())
-        {
-            let hints = found
-                .parse_args::<ModelHints>()
-                .unwrap_or_else(|_| panic!("Arguments for sqlx_model_hints {:?}", found));
+        if let Some(hints) = field_hints(&field) {
             let db_type = hints.ty.to_string();
             let mut field_position = args.len();
 
@@ -725,18 +1174,16 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
             for (comparison_ident, operator, rust_type, simple_builder) in comparisons.into_iter() {
                 comparison_idents.push(comparison_ident.clone());
                 comparison_types.push(rust_type.clone());
-                where_clauses.push(format!(
-                    "(NOT ${}::boolean OR {} {} ${}::{})",
-                    field_position + 1,
-                    &ident,
-                    operator,
-                    field_position + 2,
-                    &db_type
-                ));
-                field_position += 2;
-
-                args.push(quote! { self.#comparison_ident.is_some() });
+                let before = format!("{} {} ", column, operator);
+                let after = format!("::{}", &db_type);
+                field_position += 1;
+                where_clauses.push(format!("{}${}{}", before, field_position, after));
                 args.push(quote! { &self.#comparison_ident as &Option<#rust_type> });
+                condition_pushes.push(quote! {
+                  if let Some(value) = &self.#comparison_ident {
+                    query.condition().push(#before).push_bind(value.clone()).push(#after);
+                  }
+                });
 
                 if simple_builder {
                     builder_method_simple_idents.push(comparison_ident.clone());
@@ -762,174 +1209,464 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
             for (comparison_ident, operator, rust_type) in field_in_comparisons.into_iter() {
                 comparison_idents.push(comparison_ident.clone());
                 comparison_types.push(rust_type.clone());
-                where_clauses.push(format!(
-                    "(NOT ${}::boolean OR {} {}(CAST(${} as {}[])) )",
-                    field_position + 1,
-                    &ident,
-                    operator,
-                    field_position + 2,
-                    &db_type
-                ));
-                field_position += 2;
-
-                args.push(quote! { self.#comparison_ident.is_some() });
+                let before = format!("{} {}(CAST(", column, operator);
+                let after = format!(" as {}[]))", &db_type);
+                field_position += 1;
+                where_clauses.push(format!("{}${}{}", before, field_position, after));
                 args.push(quote! { &self.#comparison_ident as &Option<#rust_type> });
+                condition_pushes.push(quote! {
+                  if let Some(value) = &self.#comparison_ident {
+                    query.condition().push(#before).push_bind(value.clone()).push(#after);
+                  }
+                });
 
                 builder_method_simple_idents.push(comparison_ident.clone());
                 builder_method_simple_types.push(rust_type.clone());
             }
 
-            if hints.op_is_set {
-                field_position += 1;
-                let is_set_field_ident = format_ident!("{}_is_set", ident);
-                let bool_type: syn::Type = syn::parse_quote! { bool };
-                comparison_idents.push(is_set_field_ident.clone());
-                comparison_types.push(bool_type.clone());
-                where_clauses.push(
-                    format!(
-                      "(${}::boolean IS NULL OR ((${}::boolean AND {} IS NOT NULL) OR (NOT ${}::boolean AND {} IS NULL)))",
-                      field_position,
-                      field_position,
-                      &ident,
-                      field_position,
-                      &ident,
-                    )
-                );
-                args.push(quote! { self.#is_set_field_ident });
-                builder_method_simple_idents.push(is_set_field_ident.clone());
-                builder_method_simple_types.push(bool_type);
+            // Sums of integers may not fit the column type, and averages are rarely whole.
+            let i64_ty: syn::Type = syn::parse_quote! { i64 };
+            let f64_ty: syn::Type = syn::parse_quote! { f64 };
+            let numeric_aggregates = match db_type.as_str() {
+                "int2" | "int4" | "int8" | "smallint" | "integer" | "bigint" => {
+                    Some((("::int8", &i64_ty), ("::float8", &f64_ty)))
+                }
+                "float4" | "float8" | "real" => Some((("", &flat_ty), ("::float8", &f64_ty))),
+                "numeric" | "decimal" => Some((("", &flat_ty), ("", &flat_ty))),
+                _ => None,
+            };
+            let is_temporal = matches!(
+                db_type.as_str(),
+                "timestamptz" | "timestamp" | "date" | "time"
+            );
+
+            let mut aggregates = vec![];
+            if let Some(((sum_cast, sum_ty), (avg_cast, avg_ty))) = numeric_aggregates {
+                aggregates.push(("sum", sum_cast, sum_ty));
+                aggregates.push(("avg", avg_cast, avg_ty));
+            }
+            if numeric_aggregates.is_some() || is_temporal {
+                aggregates.push(("min", "", &flat_ty));
+                aggregates.push(("max", "", &flat_ty));
             }
-        };
-    }
 
-    let sort_field_pos = args.len() + 1;
-    let desc_field_pos = args.len() + 2;
-    let limit_field_pos = args.len() + 3;
-    let offset_field_pos = args.len() + 4;
-    args.push(quote! { self.order_by.map(|i| format!("{:?}", i)) as Option<String> });
-    args.push(quote! { self.desc as bool });
-    args.push(quote! { self.limit as Option<i64> });
-    args.push(quote! { self.offset as Option<i64> });
+            for (function, cast, rust_type) in aggregates.into_iter() {
+                let expr = format!("{}({}){}", function, column, cast);
+                aggregate_idents.push(format_ident!("{}_{}", function, ident));
+                aggregate_by_idents.push(format_ident!("{}_{}_by", function, ident));
+                aggregate_types.push(rust_type.clone());
+                aggregate_starts.push(LitStr::new(
+                    &format!("SELECT {} FROM {}", expr, table_name),
+                    span,
+                ));
+                aggregate_exprs.push(LitStr::new(&format!(", {}", expr), span));
+            }
+        } else if conf.primary_key.contains(ident) {
+            // Finding by id needs an equality filter even without hints, Postgres infers its type.
+            let comparison_ident = format_ident!("{}_eq", ident);
+            comparison_idents.push(comparison_ident.clone());
+            comparison_types.push(flat_ty.clone());
+            let before = format!("{} = ", column);
+            where_clauses.push(format!("{}${}", before, args.len() + 1));
+            args.push(quote! { &self.#comparison_ident as &Option<#flat_ty> });
+            condition_pushes.push(quote! {
+              if let Some(value) = &self.#comparison_ident {
+                query.condition().push(#before).push_bind(value.clone());
+              }
+            });
+            builder_method_simple_idents.push(comparison_ident);
+            builder_method_simple_types.push(flat_ty.clone());
+        };
 
-    let mut args_for_count = args.clone();
-    args_for_count.truncate(args.len() - 4);
+        let op_is_set = field_hints(&field).map(|h| h.op_is_set).unwrap_or(false);
+        if op_is_set || conf.soft_delete.as_ref() == Some(ident) {
+            let is_set_field_ident = format_ident!("{}_is_set", ident);
+            let bool_type: syn::Type = syn::parse_quote! { bool };
+            comparison_idents.push(is_set_field_ident.clone());
+            comparison_types.push(bool_type.clone());
+            let is_set = format!("{} IS NOT NULL", column);
+            let is_not_set = format!("{} IS NULL", column);
+            where_clauses.push(is_set.clone());
+            condition_pushes.push(quote! {
+              if let Some(value) = self.#is_set_field_ident {
+                query.condition().push(if value { #is_set } else { #is_not_set });
+              }
+            });
+            builder_method_simple_idents.push(is_set_field_ident.clone());
+            builder_method_simple_types.push(bool_type);
+        }
+    }
 
     let select_struct_str = LitStr::new(&select_struct.to_string(), span);
+    let struct_name_as_string = LitStr::new(&struct_name.to_string(), span);
 
-    let comparison_idents_as_str: Vec<LitStr> = comparison_idents
-        .iter()
-        .map(|i| LitStr::new(&i.to_string(), span))
-        .collect();
+    let soft_delete_filter = conf
+        .soft_delete
+        .as_ref()
+        .map(|column| format_ident!("{}_is_set", column));
 
-    let query_for_find_sort_criteria: String = field_idents
+    let comparison_defaults: Vec<TokenStream2> = comparison_idents
         .iter()
-        .map(|f| {
-            let variant_name = f.to_string().to_case(Case::UpperCamel);
-            format!(
-                r#"
-      (CASE (${} = '{}' AND NOT ${}) WHEN true THEN {} ELSE NULL END),
-      (CASE (${} = '{}' AND ${}) WHEN true THEN {} ELSE NULL END) DESC
-    "#,
-                sort_field_pos,
-                variant_name,
-                desc_field_pos,
-                f,
-                sort_field_pos,
-                variant_name,
-                desc_field_pos,
-                f
-            )
+        .map(|i| {
+            if Some(i) == soft_delete_filter.as_ref() {
+                quote! { Some(false) }
+            } else {
+                quote! { None }
+            }
         })
-        .collect::<Vec<String>>()
-        .join(",");
-
-    let query_for_find = LitStr::new(
-        &format!(
-            "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT ${} OFFSET ${}",
-            &conf.sql_select_columns,
-            table_name,
-            where_clauses.join(" AND "),
-            query_for_find_sort_criteria,
-            limit_field_pos,
-            offset_field_pos,
-        ),
-        span,
-    );
+        .collect();
 
-    let query_for_find_for_update = LitStr::new(
-        &format!(
-            "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT ${} OFFSET ${} FOR UPDATE",
-            &conf.sql_select_columns,
-            table_name,
-            where_clauses.join(" AND "),
-            query_for_find_sort_criteria,
-            limit_field_pos,
-            offset_field_pos,
+    let (select_attrs_default, soft_delete_methods, soft_delete_or) = match soft_delete_filter {
+        Some(filter) => (
+            quote! {
+              impl Default for #select_attrs_struct {
+                fn default() -> Self {
+                  Self {
+                    #(#comparison_idents: #comparison_defaults,)*
+                    order_by: None,
+                    desc: false,
+                    order: vec![],
+                    limit: None,
+                    offset: None,
+                    after: None,
+                    before: None,
+                  }
+                }
+              }
+            },
+            quote! {
+              pub fn with_deleted(mut self) -> Self {
+                self.#filter = None;
+                self
+              }
+
+              pub fn only_deleted(mut self) -> Self {
+                self.#filter = Some(true);
+                self
+              }
+
+              // Soft deleted rows are filtered once, by the outermost select.
+              fn into_operand(mut self) -> Self {
+                self.#filter = None;
+                self
+              }
+            },
+            quote! { combined.#filter = self.#filter; },
         ),
-        span,
-    );
-
-    let query_for_count = LitStr::new(
-        &format!(
-            r#"SELECT count(*) as "count!" FROM {} WHERE {}"#,
-            table_name,
-            where_clauses.join(" AND "),
+        None => (
+            quote! {},
+            quote! {
+              fn into_operand(self) -> Self {
+                self
+              }
+            },
+            quote! {},
         ),
-        span,
-    );
-
-    quote! {
-      impl #hub_struct {
-        pub fn select(&self) -> #select_struct {
-          #select_struct::new(self.state.clone())
-        }
-
-        pub async fn find<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx::Result<#struct_name> {
-          self.select().id_eq(id.borrow()).one().await
-        }
-
-        pub async fn find_for_update<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx::Result<#struct_name> {
-          self.select().id_eq(id.borrow()).one_for_update().await
-        }
-
-        pub async fn find_optional<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx::Result<Option<#struct_name>> {
-          self.select().id_eq(id.borrow()).optional().await
-        }
-      }
+    };
 
-      #[sqlx_models_orm::async_trait]
-      impl sqlx_models_orm::SqlxModelHub<#struct_name> for #hub_struct {
-        fn from_state(state: #state_name) -> Self {
-          #hub_struct::new(state)
-        }
+    let select_attrs_derive_default = if conf.soft_delete.is_none() {
+        quote! { #[derive(Default)] }
+    } else {
+        quote! {}
+    };
 
-        fn select(&self) -> #select_struct {
-          self.select()
-        }
+    let primary_key = &conf.primary_key;
+    let primary_key_eq: Vec<Ident> = primary_key
+        .iter()
+        .map(|c| format_ident!("{}_eq", c))
+        .collect();
+    let destructure_id = if conf.has_composite_primary_key() {
+        quote! { let (#(#primary_key),*) = id.borrow(); }
+    } else {
+        quote! { #(let #primary_key = id.borrow();)* }
+    };
 
-        async fn find(&self, id: &#id_type) -> sqlx::Result<#struct_name> {
-          self.find(id).await
-        }
+    let comparison_idents_as_str: Vec<LitStr> = comparison_idents
+        .iter()
+        .map(|i| LitStr::new(&i.to_string(), span))
+        .collect();
 
-        async fn find_optional(&self, id: &#id_type) -> sqlx::Result<Option<#struct_name>> {
+    let select_start = LitStr::new(&format!("SELECT {}", conf.runtime_select_columns()), span);
+    let select_from = LitStr::new(&format!(" FROM {}", table_name), span);
+    let count_start = LitStr::new(&format!("SELECT count(*) FROM {}", table_name), span);
+    let scope_idents: Vec<Ident> = conf
+        .belongs_to
+        .iter()
+        .map(|a| format_ident!("{}_in_scope", a.column_name))
+        .collect();
+    let scope_idents_as_str: Vec<LitStr> = scope_idents
+        .iter()
+        .map(|i| LitStr::new(&i.to_string(), span))
+        .collect();
+    let scope_select_structs: Vec<Ident> = conf
+        .belongs_to
+        .iter()
+        .map(|a| format_ident!("Select{}Hub", a.model_name))
+        .collect();
+    let scope_starts: Vec<LitStr> = conf
+        .belongs_to
+        .iter()
+        .map(|a| LitStr::new(&format!("{} IN (", conf.column(&a.column_name)), span))
+        .collect();
+    let existence_associations: Vec<&Association> = conf.has_many.iter().collect();
+    let with_idents: Vec<Ident> = existence_associations
+        .iter()
+        .map(|a| format_ident!("with_{}", a.model_name.to_string().to_case(Case::Snake)))
+        .collect();
+    let with_any_idents: Vec<Ident> = existence_associations
+        .iter()
+        .map(|a| format_ident!("with_any_{}", a.model_name.to_string().to_case(Case::Snake)))
+        .collect();
+    let with_matching_idents: Vec<Ident> = with_idents
+        .iter()
+        .map(|i| format_ident!("{}_matching", i))
+        .collect();
+    let without_idents: Vec<Ident> = existence_associations
+        .iter()
+        .map(|a| format_ident!("without_{}", a.model_name.to_string().to_case(Case::Snake)))
+        .collect();
+    let without_matching_idents: Vec<Ident> = without_idents
+        .iter()
+        .map(|i| format_ident!("{}_matching", i))
+        .collect();
+    let with_idents_as_str: Vec<LitStr> = with_idents
+        .iter()
+        .map(|i| LitStr::new(&i.to_string(), span))
+        .collect();
+    let without_idents_as_str: Vec<LitStr> = without_idents
+        .iter()
+        .map(|i| LitStr::new(&i.to_string(), span))
+        .collect();
+    let existence_select_structs: Vec<Ident> = existence_associations
+        .iter()
+        .map(|a| format_ident!("Select{}Hub", a.model_name))
+        .collect();
+    let existence_columns: Vec<TokenStream2> = existence_associations
+        .iter()
+        .map(|a| {
+            let model_column = format_ident!("{}Column", a.model_name);
+            let variant = conf.column_variant(&a.column_name);
+            quote! { #model_column::#variant }
+        })
+        .collect();
+    let table_name_lit = LitStr::new(table_name, span);
+    let existence_outer_column = LitStr::new(conf.column(&primary_key[0]), span);
+    let preloaded_builders: Vec<String> = conf
+        .belongs_to
+        .iter()
+        .chain(conf.has_many.iter())
+        .map(|a| a.model_name.to_string().to_case(Case::Snake))
+        .collect();
+    let preload_idents: Vec<Ident> = preloaded_builders
+        .iter()
+        .map(|b| format_ident!("preload_{}", b))
+        .collect();
+    let preload_idents_as_str: Vec<LitStr> = preload_idents
+        .iter()
+        .map(|i| LitStr::new(&i.to_string(), span))
+        .collect();
+    let loader_idents: Vec<Ident> = preloaded_builders
+        .iter()
+        .map(|b| format_ident!("load_{}", b))
+        .collect();
+    let find_many = if conf.has_composite_primary_key() {
+        quote! {}
+    } else {
+        let primary_key_variant = conf.column_variant(&primary_key[0]);
+        quote! {
+          /// Finds the rows with any of the given ids, in no particular order.
+          #[doc(hidden)]
+          pub async fn find_many<T>(&self, ids: Vec<T>) -> sqlx_models_orm::Result<Vec<#struct_name>>
+          where
+            Vec<T>: for<'q> sqlx::Encode<'q, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send + 'static,
+          {
+            self.find_all_where_any(#model_column::#primary_key_variant, ids).await
+          }
+        }
+    };
+    let id_query_start = LitStr::new(
+        &format!(
+            "SELECT {} FROM {}",
+            primary_key
+                .iter()
+                .map(|k| conf.column(k))
+                .collect::<Vec<&str>>()
+                .join(", "),
+            table_name
+        ),
+        span,
+    );
+    let exists_start = LitStr::new(&format!("SELECT EXISTS(SELECT 1 FROM {}", table_name), span);
+    let pluck_idents: Vec<Ident> = field_idents
+        .iter()
+        .map(|f| format_ident!("pluck_{}", f))
+        .collect();
+    let pluck_types: Vec<Type> = conf.fields.iter().map(|f| f.ty.clone()).collect();
+    let pluck_starts: Vec<LitStr> = field_idents
+        .iter()
+        .map(|f| LitStr::new(&format!("SELECT {}", conf.column(f)), span))
+        .collect();
+    let field_names: Vec<LitStr> = field_idents
+        .iter()
+        .map(|f| LitStr::new(&f.to_string(), span))
+        .collect();
+
+    let sort_columns: Vec<LitStr> = field_idents
+        .iter()
+        .map(|f| LitStr::new(conf.column(f), span))
+        .collect();
+    let sort_names: Vec<LitStr> = field_idents
+        .iter()
+        .map(|f| LitStr::new(&conf.column_name(f), span))
+        .collect();
+    let sort_table = LitStr::new(table_name, span);
+    let sort_db_types: Vec<TokenStream2> = field_idents
+        .iter()
+        .map(|f| match conf.db_type(f) {
+            Some(db_type) => quote! { Some(#db_type) },
+            None => quote! { None },
+        })
+        .collect();
+    let sort_nullables: Vec<bool> = conf.fields.iter().map(is_option).collect();
+    let primary_key_variants: Vec<Ident> = primary_key
+        .iter()
+        .map(|i| Ident::new(&i.to_string().to_case(Case::UpperCamel), i.span()))
+        .collect();
+    let primary_key_len = primary_key.len();
+
+    /*
+      Selects are assembled at runtime with only the conditions that were set.
+      This query is never run, it's only here so that sqlx checks every column,
+      condition and argument type against the database at compile time.
+    */
+    let query_for_validation = LitStr::new(
+        &format!(
+            "SELECT {} FROM {} WHERE {} ORDER BY {}",
+            &conf.sql_select_columns,
+            table_name,
+            if where_clauses.is_empty() {
+                "true".to_string()
+            } else {
+                where_clauses.join(" AND ")
+            },
+            conf.columns.join(", "),
+        ),
+        span,
+    );
+
+    quote! {
+      impl #hub_struct {
+        pub fn select(&self) -> #select_struct {
+          #select_struct::new(self.state.clone())
+        }
+
+        fn select_by_id<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> #select_struct {
+          #destructure_id
+          self.select()#(.#primary_key_eq(#primary_key))*
+        }
+
+        pub async fn find<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx_models_orm::Result<#struct_name> {
+          let id = id.borrow();
+          self.select_by_id(id).optional().await?.ok_or_else(|| sqlx_models_orm::Error::NotFound {
+            model: #struct_name_as_string,
+            id: Some(format!("{:?}", id)),
+          })
+        }
+
+        pub async fn find_for_update<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx_models_orm::Result<#struct_name> {
+          let id = id.borrow();
+          self.select_by_id(id).one_for_update().await.map_err(|e| match e {
+            sqlx_models_orm::Error::NotFound { model, .. } => sqlx_models_orm::Error::NotFound {
+              model,
+              id: Some(format!("{:?}", id)),
+            },
+            other => other,
+          })
+        }
+
+        pub async fn find_optional<T: std::borrow::Borrow<#id_type>>(&self, id: T) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          self.select_by_id(id).optional().await
+        }
+
+        #find_many
+
+        /// Selects the rows where 'column' is any of 'values', in no particular order.
+        #[doc(hidden)]
+        pub async fn find_all_where_any<T>(&self, column: #model_column, values: Vec<T>) -> sqlx_models_orm::Result<Vec<#struct_name>>
+        where
+          Vec<T>: for<'q> sqlx::Encode<'q, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send + 'static,
+        {
+          let select = self.select();
+          let mut query = sqlx_models_orm::SelectQuery::new(format!("{}{}", #select_start, #select_from));
+          select.push_conditions(&mut query);
+          query.condition().push(column.column()).push(" = ANY(").push_bind(values).push(")");
+          let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+          let attrs = self.state.db.fetch_all(query).await #map_err?;
+          Ok(attrs.into_iter().map(|a| select.resource(a)).collect())
+        }
+      }
+
+      #[sqlx_models_orm::async_trait]
+      impl sqlx_models_orm::SqlxModelHub<#struct_name> for #hub_struct {
+        fn from_state(state: #state_name) -> Self {
+          #hub_struct::new(state)
+        }
+
+        fn select(&self) -> #select_struct {
+          self.select()
+        }
+
+        async fn find(&self, id: &#id_type) -> sqlx_models_orm::Result<#struct_name> {
+          self.find(id).await
+        }
+
+        async fn find_optional(&self, id: &#id_type) -> sqlx_models_orm::Result<Option<#struct_name>> {
           self.find_optional(id).await
         }
       }
 
+      /// The columns a select can be sorted by. They encode as varchar so custom queries can take them too.
       #[derive(sqlx::Type, Debug, Copy, Clone)]
       #[sqlx(type_name = "varchar", rename_all = "lowercase")]
       pub enum #model_order_by {
         #(#sort_variants,)*
       }
 
+      impl #model_order_by {
+        fn sort_term(&self, desc: bool, nulls_first: bool) -> sqlx_models_orm::SortTerm {
+          let (column, name, db_type, nullable) = match self {
+            #(#model_order_by::#sort_variants => (#sort_columns, #sort_names, #sort_db_types, #sort_nullables),)*
+          };
+          sqlx_models_orm::SortTerm { table: #sort_table, column, name, db_type, nullable, desc, nulls_first }
+        }
+      }
+
+      impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for #attrs_struct {
+        fn from_row(row: &'r sqlx::postgres::PgRow) -> sqlx::Result<Self> {
+          use sqlx::Row;
+          Ok(Self {
+            #(#field_idents: row.try_get_unchecked(#field_names)?,)*
+          })
+        }
+      }
+
       #[derive(Clone)]
       pub struct #select_struct {
         pub state: #state_name,
         #(pub #comparison_idents: Option<#comparison_types>,)*
         pub order_by: Option<#model_order_by>,
         pub desc: bool,
+        pub order: Vec<(#model_order_by, sqlx_models_orm::Direction, sqlx_models_orm::Nulls)>,
         pub limit: Option<i64>,
         pub offset: Option<i64>,
+        pub after: Option<sqlx_models_orm::Cursor>,
+        pub before: Option<sqlx_models_orm::Cursor>,
+        pub or: Vec<#select_struct>,
+        pub not: Vec<#select_struct>,
+        #(pub #scope_idents: Option<Box<#scope_select_structs>>,)*
+        #(pub #with_idents: Option<Box<#existence_select_structs>>,)*
+        #(pub #without_idents: Option<Box<#existence_select_structs>>,)*
+        #(pub #preload_idents: bool,)*
       }
 
       impl std::fmt::Debug for #select_struct {
@@ -937,25 +1674,72 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           f.debug_struct(#select_struct_str)
            .field("order_by", &self.order_by)
            .field("desc", &self.desc)
+           .field("order", &self.order)
            .field("limit", &self.limit)
            .field("offset", &self.offset)
+           .field("after", &self.after)
+           .field("before", &self.before)
+           .field("or", &self.or)
+           .field("not", &self.not)
+            #(.field(#scope_idents_as_str, &self.#scope_idents))*
+            #(.field(#with_idents_as_str, &self.#with_idents))*
+            #(.field(#without_idents_as_str, &self.#without_idents))*
+            #(.field(#preload_idents_as_str, &self.#preload_idents))*
             #(.field(#comparison_idents_as_str, &self.#comparison_idents))*
            .finish()
         }
       }
 
+      const _: fn(&#select_struct) = #select_struct::compile_time_sql_check;
+
       impl #select_struct {
         pub fn new(state: #state_name) -> Self {
           Self {
             state,
             order_by: None,
             desc: false,
+            order: vec![],
             limit: None,
             offset: None,
-            #(#comparison_idents: None,)*
+            after: None,
+            before: None,
+            or: vec![],
+            not: vec![],
+            #(#scope_idents: None,)*
+            #(#with_idents: None,)*
+            #(#without_idents: None,)*
+            #(#preload_idents: false,)*
+            #(#comparison_idents: #comparison_defaults,)*
           }
         }
 
+        #soft_delete_methods
+
+        /// Selects the rows matching either this select or 'other', sorted and paginated like this select.
+        /// Whether soft deleted rows are included is up to the result, starting from this select's choice.
+        pub fn or(self, other: Self) -> Self {
+          let mut combined = Self::new(self.state.clone());
+          #(combined.#comparison_idents = None;)*
+          #soft_delete_or
+          combined.order_by = self.order_by;
+          combined.desc = self.desc;
+          combined.order = self.order.clone();
+          combined.limit = self.limit;
+          combined.offset = self.offset;
+          combined.after = self.after.clone();
+          combined.before = self.before.clone();
+          #(combined.#preload_idents = self.#preload_idents;)*
+          combined.or = vec![self.into_operand(), other.into_operand()];
+          combined
+        }
+
+        /// Leaves out the rows matching all of the conditions of 'other'.
+        /// On soft delete models those rows are left out whether they are deleted or not.
+        pub fn not(mut self, other: Self) -> Self {
+          self.not.push(other.into_operand());
+          self
+        }
+
         pub fn order_by(mut self, val: #model_order_by) -> Self {
           self.order_by = Some(val.clone());
           self
@@ -971,6 +1755,33 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           self
         }
 
+        pub fn order(mut self, val: Vec<(#model_order_by, sqlx_models_orm::Direction, sqlx_models_orm::Nulls)>) -> Self {
+          self.order = val;
+          self
+        }
+
+        fn order_terms(&self) -> Vec<(#model_order_by, sqlx_models_orm::Direction, sqlx_models_orm::Nulls)> {
+          use sqlx_models_orm::{Direction, Nulls};
+          if !self.order.is_empty() {
+            return self.order.clone();
+          }
+          match self.order_by {
+            Some(o) if self.desc => vec![(o, Direction::Desc, Nulls::First)],
+            Some(o) => vec![(o, Direction::Asc, Nulls::Last)],
+            None => vec![],
+          }
+        }
+
+        fn sort_terms(&self) -> Vec<sqlx_models_orm::SortTerm> {
+          let backwards = self.before.is_some();
+          self.order_terms().into_iter().map(|(order_by, direction, nulls)| {
+            order_by.sort_term(
+              (direction == sqlx_models_orm::Direction::Desc) != backwards,
+              (nulls == sqlx_models_orm::Nulls::First) != backwards,
+            )
+          }).collect()
+        }
+
         pub fn limit(mut self, val: i64) -> Self {
           self.limit = Some(val);
           self
@@ -981,6 +1792,22 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           self
         }
 
+        pub fn after(mut self, val: sqlx_models_orm::Cursor) -> Self {
+          self.after = Some(val);
+          self.before = None;
+          self
+        }
+
+        pub fn before(mut self, val: sqlx_models_orm::Cursor) -> Self {
+          self.before = Some(val);
+          self.after = None;
+          self
+        }
+
+        fn cursor(&self) -> Option<&sqlx_models_orm::Cursor> {
+          self.after.as_ref().or(self.before.as_ref())
+        }
+
         #(
           pub fn #builder_method_simple_idents<T: std::borrow::Borrow<#builder_method_simple_types>>(mut self, val: T) -> Self {
             self.#builder_method_simple_idents = Some(val.borrow().to_owned());
@@ -996,47 +1823,405 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           }
         )*
 
+        #(
+          /// Keeps only the rows whose association is matched by 'scope'.
+          pub fn #scope_idents(mut self, scope: #scope_select_structs) -> Self {
+            self.#scope_idents = Some(Box::new(scope));
+            self
+          }
+        )*
+
+        #(
+          /// Keeps only the rows that have at least one associated row.
+          pub fn #with_any_idents(self) -> Self {
+            let scope = #existence_select_structs::new(self.state.clone());
+            self.#with_matching_idents(scope)
+          }
+
+          /// Keeps only the rows with at least one associated row matched by 'scope'.
+          pub fn #with_matching_idents(mut self, scope: #existence_select_structs) -> Self {
+            self.#with_idents = Some(Box::new(scope));
+            self
+          }
+
+          /// Keeps only the rows that have no associated rows.
+          pub fn #without_idents(self) -> Self {
+            let scope = #existence_select_structs::new(self.state.clone());
+            self.#without_matching_idents(scope)
+          }
+
+          /// Keeps only the rows with no associated rows matched by 'scope'.
+          pub fn #without_matching_idents(mut self, scope: #existence_select_structs) -> Self {
+            self.#without_idents = Some(Box::new(scope));
+            self
+          }
+        )*
+
+        #(
+          /// Loads this association for all the selected rows with a single extra query.
+          pub fn #preload_idents(mut self) -> Self {
+            self.#preload_idents = true;
+            self
+          }
+        )*
+
         pub fn use_struct(mut self, value: #select_attrs_struct) -> Self {
           #(self.#comparison_idents = value.#comparison_idents;)*
           self.order_by = value.order_by;
           self.desc = value.desc;
+          self.order = value.order;
           self.limit = value.limit;
           self.offset = value.offset;
+          self.after = value.after;
+          self.before = value.before;
           self
         }
 
-        pub async fn all(&self) -> sqlx::Result<Vec<#struct_name>> {
-          let attrs = self.state.db.fetch_all(sqlx::query_as!(#attrs_struct, #query_for_find, #(#args),*)).await?;
-          Ok(attrs.into_iter().map(|a| self.resource(a) ).collect())
+        fn push_conditions(&self, query: &mut sqlx_models_orm::SelectQuery) {
+          #(#condition_pushes)*
+
+          if !self.or.is_empty() {
+            query.open_group("((");
+            for (i, select) in self.or.iter().enumerate() {
+              if i > 0 {
+                query.separate_group(") OR (");
+              }
+              select.push_conditions(query);
+            }
+            query.close_group("))");
+          }
+
+          for select in &self.not {
+            query.open_group("NOT (");
+            select.push_conditions(query);
+            query.close_group(")");
+          }
+
+          #(
+            if let Some(scope) = &self.#scope_idents {
+              query.condition().push(#scope_starts);
+              scope.push_id_query(query);
+              query.builder.push(")");
+            }
+          )*
+
+          #(
+            if let Some(scope) = &self.#with_idents {
+              let outer = format!("{}.{}", query.qualifier(#table_name_lit), #existence_outer_column);
+              query.condition().push("EXISTS (");
+              scope.push_exists_query(#existence_columns, &outer, query);
+              query.builder.push(")");
+            }
+
+            if let Some(scope) = &self.#without_idents {
+              let outer = format!("{}.{}", query.qualifier(#table_name_lit), #existence_outer_column);
+              query.condition().push("NOT EXISTS (");
+              scope.push_exists_query(#existence_columns, &outer, query);
+              query.builder.push(")");
+            }
+          )*
+        }
+
+        /// Pushes a subquery selecting the rows matching this select whose 'column' equals 'outer'.
+        #[doc(hidden)]
+        pub fn push_exists_query(&self, column: #model_column, outer: &str, query: &mut sqlx_models_orm::SelectQuery) {
+          query.aliased_subquery("SELECT 1", #table_name_lit, |q, alias| {
+            q.condition().push(format!("{}.{} = {}", alias, column.column(), outer));
+            self.push_conditions(q);
+          });
         }
 
-        pub async fn all_for_update(&self) -> sqlx::Result<Vec<#struct_name>> {
-          let attrs = self.state.db.fetch_all(sqlx::query_as!(#attrs_struct, #query_for_find_for_update, #(#args),*)).await?;
-          Ok(attrs.into_iter().map(|a| self.resource(a) ).collect())
+        /// Pushes a subquery selecting the primary key of the rows matching this select.
+        #[doc(hidden)]
+        pub fn push_id_query(&self, query: &mut sqlx_models_orm::SelectQuery) {
+          query.subquery(#id_query_start, |q| self.push_conditions(q));
         }
 
-        pub async fn count(&self) -> sqlx::Result<i64> {
-          self.state.db.fetch_one_scalar(sqlx::query_scalar!(#query_for_count, #(#args_for_count),*)).await
+        fn select_query(&self, init: &str, for_page: bool) -> sqlx_models_orm::Result<sqlx_models_orm::SelectQuery> {
+          self.columns_query(init, #select_start, for_page)
         }
 
-        pub async fn one(&self) -> sqlx::Result<#struct_name> {
-          let attrs = self.state.db.fetch_one(sqlx::query_as!(#attrs_struct, #query_for_find, #(#args),*)).await?;
-          Ok(self.resource(attrs))
+        // Like select_query, but selecting something other than the model's columns.
+        fn columns_query(&self, init: &str, select: &str, for_page: bool) -> sqlx_models_orm::Result<sqlx_models_orm::SelectQuery> {
+          let mut terms = self.sort_terms();
+
+          // Rows are also sorted by primary key when paginating with cursors, as a tiebreaker.
+          if for_page || self.cursor().is_some() {
+            let desc = terms.first().map(|t| t.desc).unwrap_or(self.before.is_some());
+            terms.extend(vec![#(#model_order_by::#primary_key_variants.sort_term(desc, false)),*]);
+          }
+
+          let cursor_columns = if for_page {
+            sqlx_models_orm::SelectQuery::cursor_columns(&terms)
+          } else {
+            String::new()
+          };
+
+          let mut query = sqlx_models_orm::SelectQuery::new(format!("{}{}{}{}", init, select, cursor_columns, #select_from));
+          self.push_conditions(&mut query);
+
+          if let Some(cursor) = self.cursor() {
+            let values: Vec<Option<String>> = cursor.values.iter().cloned()
+              .chain(cursor.primary_key.iter().cloned().map(Some))
+              .collect();
+            if cursor.primary_key.len() != #primary_key_len || values.len() != terms.len() {
+              return Err(sqlx_models_orm::Error::InvalidCursor(cursor.to_string()));
+            }
+            query.after(&terms, &values);
+          }
+
+          query.order_by(&terms);
+          query.limit_offset(self.limit, self.offset);
+          Ok(query)
         }
 
-        pub async fn one_for_update(&self) -> sqlx::Result<#struct_name> {
-          let attrs = self.state.db.fetch_one(sqlx::query_as!(#attrs_struct, #query_for_find_for_update, #(#args),*)).await?;
-          Ok(self.resource(attrs))
+        /// Never run, it only has sqlx check the select's SQL against the database at compile time.
+        fn compile_time_sql_check(&self) {
+          let _ = sqlx::query_as!(#attrs_struct, #query_for_validation, #(#args),*);
         }
 
-        pub async fn optional(&self) -> sqlx::Result<Option<#struct_name>> {
-          let attrs = self.state.db.fetch_optional(sqlx::query_as!(#attrs_struct, #query_for_find, #(#args),*)).await?;
-          Ok(attrs.map(|a| self.resource(a)))
+        async fn fetch_all_attrs(&self, for_update: bool) -> sqlx_models_orm::Result<Vec<#attrs_struct>> {
+          let mut query = self.select_query("", false)?;
+          if for_update {
+            query.builder.push(" FOR UPDATE");
+          }
+          let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+          let mut attrs = self.state.db.fetch_all(query).await #map_cursor_err?;
+          if self.before.is_some() {
+            attrs.reverse();
+          }
+          Ok(attrs)
+        }
+
+        pub async fn all(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let attrs = self.fetch_all_attrs(false).await?;
+          self.resources(attrs).await
+        }
+
+        pub async fn all_for_update(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let attrs = self.fetch_all_attrs(true).await?;
+          self.resources(attrs).await
+        }
+
+        /// Streams the selected rows without loading them all at once.
+        /// Rows come in query order, so selects using before() stream backwards.
+        /// Inside a transaction, awaiting other queries on it before the stream is dropped deadlocks.
+        pub fn stream(&self) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<#struct_name>> {
+          let select = self.clone();
+          Box::pin(sqlx_models_orm::async_stream::try_stream! {
+            let mut query = select.select_query("", false)?;
+            let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+            let mut rows = select.state.db.fetch(query);
+            while let Some(attrs) = sqlx_models_orm::futures_util::TryStreamExt::try_next(&mut rows).await #map_select_cursor_err? {
+              yield select.resource(attrs);
+            }
+          })
+        }
+
+        /// Streams the selected rows as CSV using COPY, with the field names as header.
+        pub fn copy_out(&self) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<sqlx_models_orm::Bytes>> {
+          let select = self.clone();
+          let cursor = self.cursor().cloned();
+          let chunks = sqlx_models_orm::SelectQuery::copy_out(self.state.db.clone(), move |init| select.select_query(init, false));
+          Box::pin(sqlx_models_orm::futures_util::TryStreamExt::map_err(chunks, move |e| match e {
+            sqlx_models_orm::Error::Sqlx(e) => sqlx_models_orm::Error::from_sqlx_with_cursor(#struct_name_as_string, e, cursor.as_ref()),
+            e => e,
+          }))
+        }
+
+        /// Walks the selected rows by primary key, yielding them in batches.
+        /// Any sorting, limit, offset or cursor in this select is ignored.
+        pub fn in_batches(&self, batch_size: i64) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<Vec<#struct_name>>> {
+          let mut select = self.clone();
+          select.order_by = None;
+          select.desc = false;
+          select.order = vec![];
+          select.offset = None;
+          select.after = None;
+          select.before = None;
+          select.limit = Some(batch_size.max(1));
+
+          Box::pin(sqlx_models_orm::async_stream::try_stream! {
+            loop {
+              let page = select.page().await?;
+              if !page.items.is_empty() {
+                yield page.items;
+              }
+              match page.next {
+                Some(next) => select.after = Some(next),
+                None => break,
+              }
+            }
+          })
+        }
+
+        /// Calls 'f' with each selected row, loading them in batches. See in_batches.
+        pub async fn find_each<F, Fut>(&self, batch_size: i64, mut f: F) -> sqlx_models_orm::Result<()>
+        where
+          F: FnMut(#struct_name) -> Fut,
+          Fut: std::future::Future<Output = sqlx_models_orm::Result<()>>,
+        {
+          let mut batches = self.in_batches(batch_size);
+          while let Some(batch) = sqlx_models_orm::futures_util::TryStreamExt::try_next(&mut batches).await? {
+            for item in batch {
+              f(item).await?;
+            }
+          }
+          Ok(())
+        }
+
+        pub async fn page(&self) -> sqlx_models_orm::Result<sqlx_models_orm::CursorPage<#struct_name>> {
+          let mut rows = self.clone().maybe_limit(self.limit.map(|l| l + 1)).page_rows().await?;
+
+          let has_more = self.limit.map(|l| rows.len() as i64 > l).unwrap_or(false);
+          if has_more {
+            rows.pop();
+          }
+
+          let backwards = self.before.is_some();
+          if backwards {
+            rows.reverse();
+          }
+
+          let first = rows.first().map(|(_, cursor)| cursor.clone());
+          let last = rows.last().map(|(_, cursor)| cursor.clone());
+          let (next, previous) = if backwards {
+            (last, if has_more { first } else { None })
+          } else {
+            (if has_more { last } else { None }, if self.after.is_some() { first } else { None })
+          };
+
+          Ok(sqlx_models_orm::CursorPage {
+            items: self.resources(rows.into_iter().map(|(a, _)| a).collect()).await?,
+            next,
+            previous,
+          })
+        }
+
+        pub async fn paginate(&self, page: i64, per_page: i64) -> sqlx_models_orm::Result<sqlx_models_orm::Page<#struct_name>> {
+          let page = page.max(1);
+          let per_page = per_page.max(1);
+          let items = self.clone().limit(per_page).offset((page - 1) * per_page).all().await?;
+          let total = self.count().await?;
+          Ok(sqlx_models_orm::Page::new(items, page, per_page, total))
+        }
+
+        async fn page_rows(&self) -> sqlx_models_orm::Result<Vec<(#attrs_struct, sqlx_models_orm::Cursor)>> {
+          let mut query = self.select_query("", true)?;
+          let terms = self.order_terms().len();
+          let query = query.builder.build().try_map(move |row| {
+            let attrs = <#attrs_struct as sqlx::FromRow<_>>::from_row(&row)?;
+            let mut values = sqlx_models_orm::SelectQuery::cursor_values(&row, terms + #primary_key_len)?;
+            let primary_key = values.split_off(terms).into_iter().map(|v| v.unwrap_or_default()).collect();
+            Ok((attrs, sqlx_models_orm::Cursor { values, primary_key }))
+          });
+          self.state.db.fetch_all(query).await #map_cursor_err
+        }
+
+        fn maybe_limit(mut self, val: Option<i64>) -> Self {
+          self.limit = val;
+          self
+        }
+
+        pub async fn count(&self) -> sqlx_models_orm::Result<i64> {
+          let mut query = sqlx_models_orm::SelectQuery::new(#count_start);
+          self.push_conditions(&mut query);
+          self.state.db.fetch_one_scalar(query.builder.build_query_scalar()).await #map_err
+        }
+
+        #(
+          /// Aggregates the selected rows, None when there are none.
+          pub async fn #aggregate_idents(&self) -> sqlx_models_orm::Result<Option<#aggregate_types>> {
+            let mut query = sqlx_models_orm::SelectQuery::new(#aggregate_starts);
+            self.push_conditions(&mut query);
+            self.state.db.fetch_one_scalar(query.builder.build_query_scalar()).await #map_err
+          }
+
+          /// Like the aggregate above, for each value of 'column', sorted by that value.
+          pub async fn #aggregate_by_idents(&self, column: #model_column) -> sqlx_models_orm::Result<Vec<(#model_value, Option<#aggregate_types>)>> {
+            self.grouped(column, #aggregate_exprs).await
+          }
+        )*
+
+        /// Counts the selected rows for each value of 'column', sorted by that value.
+        pub async fn group_by(&self, column: #model_column) -> sqlx_models_orm::Result<Vec<(#model_value, i64)>> {
+          self.grouped(column, ", count(*)").await
+        }
+
+        async fn grouped<T>(&self, column: #model_column, aggregate: &str) -> sqlx_models_orm::Result<Vec<(#model_value, T)>>
+        where
+          T: for<'r> sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send + Unpin,
+        {
+          let mut query = sqlx_models_orm::SelectQuery::new(format!("SELECT {}{}{}", column.column(), aggregate, #select_from));
+          self.push_conditions(&mut query);
+          query.builder.push(" GROUP BY 1 ORDER BY 1");
+          let query = query.builder.build().try_map(move |row| {
+            use sqlx::Row;
+            Ok((#model_value::from_row(column, &row, 0)?, row.try_get(1)?))
+          });
+          self.state.db.fetch_all(query).await #map_err
+        }
+
+        /// Whether any row matches this select's conditions.
+        pub async fn exists(&self) -> sqlx_models_orm::Result<bool> {
+          let mut query = sqlx_models_orm::SelectQuery::new(#exists_start);
+          self.push_conditions(&mut query);
+          query.builder.push(")");
+          self.state.db.fetch_one_scalar(query.builder.build_query_scalar()).await #map_err
+        }
+
+        #(
+          /// Fetches only this field from the selected rows, honoring sorting, limit, offset and cursors.
+          pub async fn #pluck_idents(&self) -> sqlx_models_orm::Result<Vec<#pluck_types>> {
+            let mut query = self.columns_query("", #pluck_starts, false)?;
+            let query = query.builder.build().try_map(|row| {
+              use sqlx::Row;
+              row.try_get_unchecked(0)
+            });
+            let mut values = self.state.db.fetch_all(query).await #map_cursor_err?;
+            if self.before.is_some() {
+              values.reverse();
+            }
+            Ok(values)
+          }
+        )*
+
+        /// The query plan Postgres would use for this select.
+        pub async fn explain(&self) -> sqlx_models_orm::Result<String> {
+          let mut query = self.select_query("EXPLAIN ", false)?;
+          let lines: Vec<String> = self.state.db.fetch_all_scalar(query.builder.build_query_scalar()).await #map_cursor_err?;
+          Ok(lines.join("\n"))
+        }
+
+        pub async fn one(&self) -> sqlx_models_orm::Result<#struct_name> {
+          self.optional().await?.ok_or(sqlx_models_orm::Error::NotFound { model: #struct_name_as_string, id: None })
+        }
+
+        pub async fn one_for_update(&self) -> sqlx_models_orm::Result<#struct_name> {
+          let attrs = self.clone().limit(1).fetch_all_attrs(true).await?;
+          self.resources(attrs).await?.into_iter().next()
+            .ok_or(sqlx_models_orm::Error::NotFound { model: #struct_name_as_string, id: None })
+        }
+
+        pub async fn optional(&self) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          let attrs = self.clone().limit(1).fetch_all_attrs(false).await?;
+          Ok(self.resources(attrs).await?.into_iter().next())
         }
 
         fn resource(&self, attrs: #attrs_struct) -> #struct_name {
           #struct_name::new(self.state.clone(), attrs)
         }
+
+        async fn resources(&self, attrs: Vec<#attrs_struct>) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          #[allow(unused_mut)]
+          let mut items: Vec<#struct_name> = attrs.into_iter().map(|a| self.resource(a)).collect();
+          #(
+            if self.#preload_idents {
+              #hub_struct::new(self.state.clone()).#loader_idents(&mut items).await?;
+            }
+          )*
+          Ok(items)
+        }
       }
 
       #[sqlx_models_orm::async_trait]
@@ -1057,6 +2242,10 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           self.desc(val)
         }
 
+        fn order(self, val: Vec<(#model_order_by, sqlx_models_orm::Direction, sqlx_models_orm::Nulls)>) -> Self {
+          self.order(val)
+        }
+
         fn limit(self, val: i64) -> Self {
           self.limit(val)
         }
@@ -1065,43 +2254,78 @@ fn build_select(conf: &SqlxModelConf) -> TokenStream2 {
           self.offset(val)
         }
 
+        fn after(self, val: sqlx_models_orm::Cursor) -> Self {
+          self.after(val)
+        }
+
+        fn before(self, val: sqlx_models_orm::Cursor) -> Self {
+          self.before(val)
+        }
+
         fn use_struct(self, value: #select_attrs_struct) -> Self {
           self.use_struct(value)
         }
 
-        async fn all(&self) -> sqlx::Result<Vec<#struct_name>> {
+        async fn all(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
           self.all().await
         }
 
-        async fn count(&self) -> sqlx::Result<i64> {
+        async fn count(&self) -> sqlx_models_orm::Result<i64> {
           self.count().await
         }
 
-        async fn one(&self) -> sqlx::Result<#struct_name> {
+        async fn exists(&self) -> sqlx_models_orm::Result<bool> {
+          self.exists().await
+        }
+
+        async fn one(&self) -> sqlx_models_orm::Result<#struct_name> {
           self.one().await
         }
 
-        async fn optional(&self) -> sqlx::Result<Option<#struct_name>> {
+        async fn optional(&self) -> sqlx_models_orm::Result<Option<#struct_name>> {
           self.optional().await
         }
+
+        fn stream(&self) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<#struct_name>> {
+          self.stream()
+        }
+
+        fn in_batches(&self, batch_size: i64) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<Vec<#struct_name>>> {
+          self.in_batches(batch_size)
+        }
+
+        async fn page(&self) -> sqlx_models_orm::Result<sqlx_models_orm::CursorPage<#struct_name>> {
+          self.page().await
+        }
+
+        async fn paginate(&self, page: i64, per_page: i64) -> sqlx_models_orm::Result<sqlx_models_orm::Page<#struct_name>> {
+          self.paginate(page, per_page).await
+        }
       }
 
-      #[derive(Debug, Default)]
+      #[derive(Debug)]
+      #select_attrs_derive_default
       pub struct #select_attrs_struct {
         #(pub #comparison_idents: Option<#comparison_types>,)*
         pub order_by: Option<#model_order_by>,
         pub desc: bool,
+        pub order: Vec<(#model_order_by, sqlx_models_orm::Direction, sqlx_models_orm::Nulls)>,
         pub limit: Option<i64>,
         pub offset: Option<i64>,
+        pub after: Option<sqlx_models_orm::Cursor>,
+        pub before: Option<sqlx_models_orm::Cursor>,
       }
+
+      #select_attrs_default
     }
 }
 
 fn build_queries(conf: &SqlxModelConf) -> Vec<TokenStream2> {
+    let map_err = conf.map_err();
     let state_name = &conf.state_name;
     let struct_name = &conf.struct_name;
     let hub_struct = &conf.hub_struct;
-    let table_name = &conf.table_name;
+    let table_name = conf.table_for_queries();
     let attrs_struct = &conf.attrs_struct;
     let span = conf.struct_name.span();
 
@@ -1120,13 +2344,15 @@ fn build_queries(conf: &SqlxModelConf) -> Vec<TokenStream2> {
       sql.value()
     ), span);
 
+    // Postgres before 16 rejects subqueries in FROM without an alias.
     let query_for_count = LitStr::new(&format!(
-      r#"SELECT count(*) as "count!" FROM (SELECT 1 FROM {} WHERE {})"#,
+      r#"SELECT count(*) as "count!" FROM (SELECT 1 FROM {} WHERE {}) AS query"#,
       table_name,
       sql.value()
     ), span);
 
     quote!{
+      #[derive(Clone)]
       pub struct #query_struct_name {
         state: #state_name,
         #args
@@ -1137,23 +2363,34 @@ fn build_queries(conf: &SqlxModelConf) -> Vec<TokenStream2> {
           #struct_name::new(self.state.clone(), attrs)
         }
 
-        pub async fn all(&self) -> sqlx::Result<Vec<#struct_name>> {
-          let attrs = self.state.db.fetch_all(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await?;
+        pub async fn all(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let attrs = self.state.db.fetch_all(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await #map_err?;
           Ok(attrs.into_iter().map(|a| self.init(a) ).collect())
         }
 
-        pub async fn one(&self) -> sqlx::Result<#struct_name> {
-          let attrs = self.state.db.fetch_one(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await?;
+        pub async fn one(&self) -> sqlx_models_orm::Result<#struct_name> {
+          let attrs = self.state.db.fetch_one(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await #map_err?;
           Ok(self.init(attrs))
         }
 
-        pub async fn optional(&self) -> sqlx::Result<Option<#struct_name>> {
-          let attrs = self.state.db.fetch_optional(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await?;
+        pub async fn optional(&self) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          let attrs = self.state.db.fetch_optional(sqlx::query_as!(#attrs_struct, #query, #(&self.#arg_names as &#arg_types),*)).await #map_err?;
           Ok(attrs.map(|a| self.init(a)))
         }
 
-        pub async fn count(&self) -> sqlx::Result<i64> {
-          self.state.db.fetch_one_scalar(sqlx::query_scalar!(#query_for_count, #(&self.#arg_names as &#arg_types),*)).await
+        pub async fn count(&self) -> sqlx_models_orm::Result<i64> {
+          self.state.db.fetch_one_scalar(sqlx::query_scalar!(#query_for_count, #(&self.#arg_names as &#arg_types),*)).await #map_err
+        }
+
+        /// Streams the rows without loading them all at once, see the select's stream().
+        pub fn stream(&self) -> sqlx_models_orm::BoxStream<'static, sqlx_models_orm::Result<#struct_name>> {
+          let custom_query = self.clone();
+          Box::pin(sqlx_models_orm::async_stream::try_stream! {
+            let mut rows = custom_query.state.db.fetch(sqlx::query_as!(#attrs_struct, #query, #(&custom_query.#arg_names as &#arg_types),*));
+            while let Some(attrs) = sqlx_models_orm::futures_util::TryStreamExt::try_next(&mut rows).await #map_err? {
+              yield custom_query.init(attrs);
+            }
+          })
         }
       }
 
@@ -1168,6 +2405,7 @@ fn build_queries(conf: &SqlxModelConf) -> Vec<TokenStream2> {
 }
 
 fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
+    let map_err = conf.map_err();
     let span = conf.struct_name.span();
     let state_name = &conf.state_name;
     let struct_name = &conf.struct_name;
@@ -1180,18 +2418,10 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
         .fields
         .clone()
         .into_iter()
-        .filter(|field| {
-            match field
-                .attrs
-                .iter()
-                .find(|a| a.path == parse_str("sqlx_model_hints").unwrap())
-            {
-                None => true,
-                Some(found) => {
-                    let hint: ModelHints = found.parse_args().unwrap();
-                    !hint.default
-                }
-            }
+        .filter(|field| !conf.is_automatic(field.ident.as_ref().unwrap()))
+        .filter(|field| match field_hints(field) {
+            None => true,
+            Some(hint) => !hint.default,
         })
         .collect();
 
@@ -1221,11 +2451,49 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
 
     let insert_struct = format_ident!("Insert{}Hub", &struct_name);
     let insert_struct_as_string = LitStr::new(&insert_struct.to_string(), span);
+    let struct_name_as_string = LitStr::new(&struct_name.to_string(), span);
     let insert_attrs_struct = format_ident!("Insert{}", &struct_name);
 
+    let automatic_fields: Vec<&Ident> = conf.auto_now_add.iter().chain(&conf.auto_now).collect();
+    let automatic_types: Vec<Type> = automatic_fields
+        .iter()
+        .map(|ident| {
+            conf.fields
+                .iter()
+                .find(|f| f.ident.as_ref() == Some(*ident))
+                .unwrap()
+                .ty
+                .clone()
+        })
+        .collect();
+    let automatic_upsert_values: Vec<TokenStream2> = automatic_fields
+        .iter()
+        .zip(&automatic_types)
+        .map(|(ident, ty)| {
+            if is_option_type(ty) {
+                quote! { #ident.clone().flatten() }
+            } else {
+                quote! { #ident.clone() }
+            }
+        })
+        .collect();
+    let automatic_overrides: Vec<TokenStream2> = automatic_fields
+        .iter()
+        .zip(&automatic_types)
+        .map(|(ident, ty)| {
+            if is_option_type(ty) {
+                quote! { &self.#ident.clone().flatten() as &#ty }
+            } else {
+                quote! { &self.#ident as &Option<#ty> }
+            }
+        })
+        .collect();
+
     let column_names_to_insert = fields_for_insert_idents
         .iter()
-        .map(|f| f.to_string())
+        .chain(automatic_fields.iter().copied())
+        .chain(&conf.lock_version)
+        .map(|f| conf.column(f).to_string())
         .collect::<Vec<String>>()
         .join(", \n");
 
@@ -1233,29 +2501,223 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
         .iter()
         .enumerate()
         .map(|(n, _)| format!("${}", n + 1))
+        .chain(
+            automatic_fields
+                .iter()
+                .enumerate()
+                .map(|(n, f)| conf.now_unless(f, &format!("${}", fields_for_insert.len() + n + 1))),
+        )
+        .chain(conf.lock_version.iter().map(|_| "0".to_string()))
         .collect::<Vec<String>>()
         .join(", ");
 
+    // Models where the database provides every value have nothing to list.
+    let has_insert_columns = !column_names_to_insert.is_empty();
+    let insert_values = if has_insert_columns {
+        format!(
+            "({}) VALUES ({})",
+            column_names_to_insert, column_names_to_insert_positions
+        )
+    } else {
+        "DEFAULT VALUES".to_string()
+    };
+
     let query_for_insert = LitStr::new(
         &format!(
-            "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+            "INSERT INTO {} {} RETURNING {}",
+            table_name, insert_values, &conf.sql_select_columns,
+        ),
+        span,
+    );
+
+    let query_for_insert_no_conflict = LitStr::new(
+        &format!(
+            "INSERT INTO {} {} ON CONFLICT ({}) DO UPDATE SET {} = EXCLUDED.{} RETURNING {}",
             table_name,
-            column_names_to_insert,
-            column_names_to_insert_positions,
+            insert_values,
+            conf.primary_key
+                .iter()
+                .map(|c| conf.column(c))
+                .collect::<Vec<&str>>()
+                .join(", "),
+            conf.column(&conf.primary_key[0]),
+            conf.column(&conf.primary_key[0]),
             &conf.sql_select_columns,
         ),
         span,
     );
 
-    let query_for_insert_no_conflict = LitStr::new(
+    let insert_many_start = LitStr::new(
+        &format!(
+            "INSERT INTO {} ({}) ",
+            table_name,
+            column_names_to_insert.replace('\n', "")
+        ),
+        span,
+    );
+    let insert_defaults_start = LitStr::new(
+        &format!("INSERT INTO {} SELECT FROM generate_series(1, ", table_name),
+        span,
+    );
+    let runtime_returning = LitStr::new(
+        &format!(" RETURNING {}", conf.runtime_select_columns()),
+        span,
+    );
+    let copy_in_columns = fields_for_insert_idents
+        .iter()
+        .map(|f| conf.column(f))
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let copy_in_fields = fields_for_insert.len() as i16;
+    // COPY can't fill columns with now(), so those rows go through a temporary table first.
+    let copy_in = if automatic_fields.is_empty() && conf.lock_version.is_none() {
+        let statement = LitStr::new(
+            &format!(
+                "COPY {} ({}) FROM STDIN WITH (FORMAT binary)",
+                table_name, copy_in_columns
+            ),
+            span,
+        );
+        quote! { self.state.db.copy_in(#statement, chunks).await #map_err }
+    } else {
+        let create_staging = LitStr::new(
+            &format!(
+                " ON COMMIT DROP AS SELECT {} FROM {} WITH NO DATA",
+                copy_in_columns, table_name
+            ),
+            span,
+        );
+        let insert_from_staging = LitStr::new(
+            &format!(
+                "INSERT INTO {} ({}) SELECT {} FROM ",
+                table_name,
+                column_names_to_insert.replace('\n', ""),
+                fields_for_insert_idents
+                    .iter()
+                    .map(|f| conf.column(f).to_string())
+                    .chain(automatic_fields.iter().map(|_| "now()".to_string()))
+                    .chain(conf.lock_version.iter().map(|_| "0".to_string()))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            span,
+        );
+        quote! {
+          let tx = if self.state.db.in_transaction().await {
+            None
+          } else {
+            Some(self.state.db.transaction().await #map_err?)
+          };
+          let db = tx.as_ref().unwrap_or(&self.state.db);
+          let table = sqlx_models_orm::temp_table_name("copy_in");
+          db.execute(sqlx::query(&format!("CREATE TEMP TABLE {}{}", table, #create_staging))).await #map_err?;
+          db.copy_in(&format!("COPY {} FROM STDIN WITH (FORMAT binary)", table), chunks).await #map_err?;
+          let copied = db.execute(sqlx::query(&format!("{}{}", #insert_from_staging, table))).await #map_err?.rows_affected();
+          db.execute(sqlx::query(&format!("DROP TABLE {}", table))).await #map_err?;
+          if let Some(tx) = tx {
+            tx.commit().await #map_err?;
+          }
+          Ok(copied)
+        }
+    };
+
+    let binds_per_row = fields_for_insert.len().max(1);
+    let automatic_values: Vec<LitStr> = automatic_fields
+        .iter()
+        .map(|_| LitStr::new("now()", span))
+        .chain(conf.lock_version.iter().map(|_| LitStr::new("0", span)))
+        .collect();
+    let lock_version_values: Vec<LitStr> = conf
+        .lock_version
+        .iter()
+        .map(|_| LitStr::new("0", span))
+        .collect();
+    let push_insert_row = quote! {
+      #( row.push_bind(item.#fields_for_insert_idents); )*
+      #( row.push(#automatic_values); )*
+    };
+    let push_upsert_row = quote! {
+      #( row.push_bind(item.#fields_for_insert_idents); )*
+      #( row.push("COALESCE(").push_bind_unseparated(#automatic_upsert_values).push_unseparated(", now())"); )*
+      #( row.push(#lock_version_values); )*
+    };
+
+    let model_column = format_ident!("{}Column", &struct_name);
+    let insert_column = format_ident!("Insert{}Column", &struct_name);
+    let conflict_struct = format_ident!("Insert{}Conflict", &struct_name);
+    let insert_column_variants: Vec<Ident> = fields_for_insert_idents
+        .iter()
+        .map(|i| conf.column_variant(i))
+        .collect();
+    let insert_column_names: Vec<LitStr> = fields_for_insert_idents
+        .iter()
+        .map(|i| LitStr::new(conf.column(i), span))
+        .collect();
+    let upsert_start = LitStr::new(
         &format!(
-    "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT (id) DO UPDATE SET id = {}.id RETURNING {}",
-    table_name,
-    column_names_to_insert,
-    column_names_to_insert_positions,
-    table_name,
-    &conf.sql_select_columns,
-  ),
+            "INSERT INTO {} AS existing ({}) ",
+            table_name,
+            column_names_to_insert.replace('\n', "")
+        ),
+        span,
+    );
+    let upsert_defaults = LitStr::new(
+        &format!("INSERT INTO {} AS existing DEFAULT VALUES", table_name),
+        span,
+    );
+
+    let (insert_many_chunk, copy_in_body, upsert_values) = if has_insert_columns {
+        (
+            quote! {
+              let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(#insert_many_start);
+              query.push_values(items.by_ref().take(chunk_size), |mut row, item| {
+                #push_insert_row
+              });
+            },
+            quote! {
+              let chunks = sqlx_models_orm::BinaryCopyIn::chunks(items, |copy, item| {
+                copy.row(#copy_in_fields);
+                #( copy.field(&item.#fields_for_insert_idents)?; )*
+                Ok(())
+              });
+              #copy_in
+            },
+            quote! {
+              #( let #automatic_fields = self.insert.#automatic_fields; )*
+              let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(#upsert_start);
+              query.push_values(std::iter::once(self.insert.attrs), |mut row, item| {
+                #push_upsert_row
+              });
+            },
+        )
+    } else {
+        (
+            quote! {
+              let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(#insert_defaults_start);
+              query.push_bind(items.by_ref().take(chunk_size).count() as i64).push(")");
+            },
+            quote! {
+              let inserted = self.insert_many(items.into_iter().collect()).await?;
+              Ok(inserted.len() as u64)
+            },
+            quote! {
+              let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(#upsert_defaults);
+            },
+        )
+    };
+    let automatic_updates: Vec<LitStr> = conf
+        .auto_now
+        .iter()
+        .map(|f| format!("{0} = EXCLUDED.{0}", conf.column(f)))
+        .chain(
+            conf.lock_version
+                .iter()
+                .map(|f| format!("{0} = existing.{0} + 1", conf.column(f))),
+        )
+        .map(|set| LitStr::new(&set, span))
+        .collect();
+    let keep_existing = LitStr::new(
+        &format!("{0} = existing.{0}", conf.column(&conf.primary_key[0])),
         span,
     );
 
@@ -1265,6 +2727,44 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
         pub fn insert(&self, attrs: #insert_attrs_struct) -> #insert_struct {
           #insert_struct::new(self.state.clone(), attrs)
         }
+
+        /// Inserts all rows with multi-row INSERT statements, returning them in order.
+        /// Rows are split in chunks to stay under Postgres' limit of 65535 binds per statement,
+        /// and several chunks run in a transaction unless one is already open.
+        pub async fn insert_many(&self, items: Vec<#insert_attrs_struct>) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let chunk_size = 65535 / #binds_per_row;
+          let tx = if items.len() > chunk_size && !self.state.db.in_transaction().await {
+            Some(self.state.db.transaction().await #map_err?)
+          } else {
+            None
+          };
+          let db = tx.as_ref().unwrap_or(&self.state.db);
+
+          let mut inserted = Vec::with_capacity(items.len());
+          let mut items = items.into_iter().peekable();
+          while items.peek().is_some() {
+            #insert_many_chunk
+            query.push(#runtime_returning);
+            let query = query.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+            let rows = db.fetch_all(query).await #map_err?;
+            inserted.extend(rows.into_iter().map(|attrs| #struct_name::new(self.state.clone(), attrs)));
+          }
+
+          if let Some(tx) = tx {
+            tx.commit().await #map_err?;
+          }
+          Ok(inserted)
+        }
+
+        /// Loads rows with COPY in binary format, the fastest way to import large amounts of data.
+        /// Returns how many rows were copied.
+        pub async fn copy_in<I>(&self, items: I) -> sqlx_models_orm::Result<u64>
+        where
+          I: IntoIterator<Item = #insert_attrs_struct>,
+          I::IntoIter: Send,
+        {
+          #copy_in_body
+        }
       }
 
       #[derive(Clone)]
@@ -1272,6 +2772,7 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
       pub struct #insert_struct {
         pub state: #state_name,
         pub attrs: #insert_attrs_struct,
+        #(#automatic_fields: Option<#automatic_types>,)*
       }
 
       impl #insert_struct {
@@ -1279,9 +2780,17 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
           state: #state_name,
           attrs: #insert_attrs_struct
         ) -> Self {
-          Self{ state, attrs }
+          Self{ state, attrs, #(#automatic_fields: None,)* }
         }
 
+        #(
+          /// Sets this field instead of leaving it to now().
+          pub fn #automatic_fields(mut self, val: #automatic_types) -> Self {
+            self.#automatic_fields = Some(val);
+            self
+          }
+        )*
+
         #(
           pub fn #fields_for_insert_idents(&self) -> &#fields_for_insert_types {
             &self.attrs.#fields_for_insert_idents
@@ -1293,31 +2802,98 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
           self
         }
 
-        pub async fn save(self) -> std::result::Result<#struct_name, sqlx::Error> {
+        pub async fn save(self) -> sqlx_models_orm::Result<#struct_name> {
           let attrs = self.state.db.fetch_one(
             sqlx::query_as!(
               #attrs_struct,
               #query_for_insert,
-              #(&self.attrs.#fields_for_insert_idents as &#fields_for_insert_types),*
+              #(&self.attrs.#fields_for_insert_idents as &#fields_for_insert_types,)*
+              #(#automatic_overrides,)*
             )
-          ).await?;
+          ).await #map_err?;
 
           Ok(#struct_name::new(self.state.clone(), attrs))
         }
 
-        pub async fn save_no_conflict(self) -> std::result::Result<#struct_name, sqlx::Error> {
+        /// Handles conflicts on the given columns, which need a unique index or constraint.
+        pub fn on_conflict(self, column: #model_column, more: Vec<#model_column>) -> #conflict_struct {
+          let columns: Vec<&str> = std::iter::once(column).chain(more).map(|c| c.column()).collect();
+          let target = format!("({})", columns.join(", "));
+          #conflict_struct { insert: self, target }
+        }
+
+        /// Handles conflicts on the named unique or exclusion constraint.
+        pub fn on_conflict_constraint(self, name: &str) -> #conflict_struct {
+          let target = format!("ON CONSTRAINT \"{}\"", name.replace('"', "\"\""));
+          #conflict_struct { insert: self, target }
+        }
+
+        /// Skips the insert when it conflicts with any unique index or constraint, returning None.
+        pub async fn do_nothing_on_conflict(self) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          #conflict_struct { insert: self, target: String::new() }.do_nothing().await
+        }
+
+        pub async fn save_no_conflict(self) -> sqlx_models_orm::Result<#struct_name> {
           let attrs = self.state.db.fetch_one(
             sqlx::query_as!(
               #attrs_struct,
               #query_for_insert_no_conflict,
-              #(&self.attrs.#fields_for_insert_idents as &#fields_for_insert_types),*
+              #(&self.attrs.#fields_for_insert_idents as &#fields_for_insert_types,)*
+              #(#automatic_overrides,)*
             )
-          ).await?;
+          ).await #map_err?;
 
           Ok(#struct_name::new(self.state.clone(), attrs))
         }
       }
 
+      #[must_use = "don't forget to say what to do on conflict"]
+      pub struct #conflict_struct {
+        insert: #insert_struct,
+        target: String,
+      }
+
+      impl #conflict_struct {
+        /// Leaves the existing row alone, returning None when there was a conflict.
+        pub async fn do_nothing(self) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          self.save(" DO NOTHING".to_string()).await
+        }
+
+        /// Updates the existing row with the given fields from the insert.
+        pub async fn do_update(self, columns: Vec<#insert_column>) -> sqlx_models_orm::Result<#struct_name> {
+          let automatic_updates: &[&str] = &[#(#automatic_updates),*];
+
+          let mut sets: Vec<String> = columns
+            .iter()
+            .map(|c| format!("{0} = EXCLUDED.{0}", c.column()))
+            .collect();
+          sets.sort();
+          sets.dedup();
+          sets.extend(automatic_updates.iter().map(|s| s.to_string()));
+          if sets.is_empty() {
+            sets.push(#keep_existing.to_string());
+          }
+
+          self.save(format!(" DO UPDATE SET {}", sets.join(", ")))
+            .await?
+            .ok_or(sqlx_models_orm::Error::NotFound { model: #struct_name_as_string, id: None })
+        }
+
+        /// Updates the existing row with every field from the insert.
+        pub async fn do_update_all(self) -> sqlx_models_orm::Result<#struct_name> {
+          self.do_update(vec![#(#insert_column::#insert_column_variants),*]).await
+        }
+
+        async fn save(self, action: String) -> sqlx_models_orm::Result<Option<#struct_name>> {
+          let state = self.insert.state;
+          #upsert_values
+          query.push(" ON CONFLICT ").push(self.target).push(action).push(#runtime_returning);
+          let query = query.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+          let attrs = state.db.fetch_optional(query).await #map_err?;
+          Ok(attrs.map(|a| #struct_name::new(state.clone(), a)))
+        }
+      }
+
       impl std::fmt::Debug for #insert_struct {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
           f.debug_struct(#insert_struct_as_string)
@@ -1328,6 +2904,21 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
         }
       }
 
+      /// The columns set by an insert, which an upsert can update.
+      #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+      pub enum #insert_column {
+        #(#insert_column_variants,)*
+      }
+
+      impl #insert_column {
+        /// The column name as used in SQL.
+        pub fn column(&self) -> &'static str {
+          match *self {
+            #(#insert_column::#insert_column_variants => #insert_column_names,)*
+          }
+        }
+      }
+
       #(#extra_struct_attributes)*
       #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
       pub struct #insert_attrs_struct {
@@ -1340,15 +2931,32 @@ fn build_insert(conf: &SqlxModelConf) -> TokenStream2 {
 }
 
 fn build_update(conf: &SqlxModelConf) -> TokenStream2 {
+    let map_err = conf.map_err();
     let span = conf.struct_name.span();
     let state_name = &conf.state_name;
     let struct_name = &conf.struct_name;
     let table_name = &conf.table_name;
     let attrs_struct = &conf.attrs_struct;
-    let fields = &conf.fields;
-    let field_idents = &conf.field_idents;
     let id_type = &conf.id_type;
-    let field_types: Vec<Type> = fields.clone().into_iter().map(|i| i.ty).collect();
+    let fields: Vec<Field> = conf
+        .fields
+        .clone()
+        .into_iter()
+        .filter(|field| !conf.is_automatic(field.ident.as_ref().unwrap()))
+        .collect();
+    let field_idents: Vec<Ident> = fields
+        .iter()
+        .map(|i| i.ident.as_ref().unwrap().clone())
+        .collect();
+    let field_types: Vec<Type> = fields.iter().map(|i| i.ty.clone()).collect();
+    let auto_now_fields: Vec<Field> = conf
+        .fields
+        .clone()
+        .into_iter()
+        .filter(|field| conf.auto_now.contains(field.ident.as_ref().unwrap()))
+        .collect();
+    let auto_now_idents = &conf.auto_now;
+    let auto_now_types: Vec<Type> = auto_now_fields.iter().map(|i| i.ty.clone()).collect();
 
     let update_struct = format_ident!("Update{}Hub", &struct_name);
     let update_attrs_struct = format_ident!("Update{}", &struct_name);
@@ -1372,43 +2980,162 @@ fn build_update(conf: &SqlxModelConf) -> TokenStream2 {
         }
     }
 
-    let column_names_to_insert = field_idents
+    for field in auto_now_fields.iter() {
+        let ty = &field.ty;
+        let ident = field.ident.as_ref().unwrap();
+        if is_option(field) {
+            args_for_update.push(quote! { &self.#ident.clone().flatten() as &#ty });
+        } else {
+            args_for_update.push(quote! { &self.#ident as &Option<#ty> });
+        }
+    }
+
+    let column_names_to_update = field_idents
         .iter()
-        .map(|f| f.to_string())
-        .collect::<Vec<String>>()
+        .chain(&conf.auto_now)
+        .chain(&conf.lock_version)
+        .map(|f| conf.column(f))
+        .collect::<Vec<&str>>()
         .join(", \n");
 
     let column_names_to_update_positions = field_idents
         .iter()
         .enumerate()
         .map(|(n, f)| {
-            let base_pos = 2 + (n * 2);
+            let base_pos = conf.primary_key.len() + 1 + (n * 2);
             format!(
                 "(CASE ${}::boolean WHEN TRUE THEN ${} ELSE {} END)",
                 base_pos,
                 base_pos + 1,
-                f.clone()
+                conf.column(f)
             )
         })
+        .chain(conf.auto_now.iter().enumerate().map(|(n, f)| {
+            let position = conf.primary_key.len() + 1 + (field_idents.len() * 2) + n;
+            conf.now_unless(f, &format!("${}", position))
+        }))
+        .chain(
+            conf.lock_version
+                .iter()
+                .map(|f| format!("{} + 1", conf.column(f))),
+        )
         .collect::<Vec<String>>()
         .join(", ");
 
+    let mut where_clause = conf.primary_key_where_clause();
+    if let Some(lock_version) = conf.lock_version.as_ref() {
+        where_clause.push_str(&format!(
+            " AND {} = ${}",
+            conf.column(lock_version),
+            conf.primary_key.len() + 1 + (field_idents.len() * 2) + conf.auto_now.len()
+        ));
+    }
+    if let Some(soft_delete) = conf.soft_delete.as_ref() {
+        where_clause.push_str(&format!(" AND {} IS NULL", conf.column(soft_delete)));
+    }
+
+    let args_for_id: Vec<TokenStream2> = if conf.has_composite_primary_key() {
+        (0..conf.primary_key.len())
+            .map(|n| {
+                let index = syn::Index::from(n);
+                quote! { self.id.#index }
+            })
+            .collect()
+    } else {
+        vec![quote! { self.id }]
+    };
+
+    let primary_key_value = conf.primary_key_value(quote! { self.attrs });
+
     let query_for_update = LitStr::new(
         &format!(
-            "UPDATE {} SET ({}) = ({}) WHERE id = $1 RETURNING {}",
+            "UPDATE {} SET ({}) = ({}) WHERE {} RETURNING {}",
             table_name,
-            column_names_to_insert,
+            column_names_to_update,
             column_names_to_update_positions,
+            where_clause,
             &conf.sql_select_columns,
         ),
         span,
     );
 
+    let struct_name_as_string = LitStr::new(&struct_name.to_string(), span);
+
+    let (
+        lock_version_field,
+        lock_version_param,
+        lock_version_from_model,
+        lock_version_arg,
+        fetch_updated,
+    ) = match conf.lock_version.as_ref() {
+        Some(lock_version) => {
+            let ty = &conf
+                .fields
+                .iter()
+                .find(|f| f.ident.as_ref() == Some(lock_version))
+                .unwrap()
+                .ty;
+            (
+                quote! { pub lock_version: #ty, },
+                quote! { , lock_version: #ty },
+                quote! { , self.attrs.#lock_version },
+                quote! { self.lock_version },
+                quote! {
+                  fetch_optional(query).await #map_err?.ok_or_else(|| {
+                    sqlx_models_orm::Error::StaleObject {
+                      model: #struct_name_as_string
+                    }
+                  })?
+                },
+            )
+        }
+        None => (
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! { fetch_one(query).await #map_err? },
+        ),
+    };
+
+    let lock_version_init = if conf.lock_version.is_some() {
+        quote! { lock_version, }
+    } else {
+        quote! {}
+    };
+
+    let select_struct = format_ident!("Select{}Hub", &struct_name);
+    let update_all_start = LitStr::new(&format!("UPDATE {} SET ", table_name), span);
+    let set_columns: Vec<LitStr> = field_idents
+        .iter()
+        .map(|f| LitStr::new(&format!("{} = ", conf.column(f)), span))
+        .collect();
+    let automatic_sets: Vec<LitStr> = conf
+        .auto_now
+        .iter()
+        .map(|f| format!("{} = now()", conf.column(f)))
+        .chain(
+            conf.lock_version
+                .iter()
+                .map(|f| format!("{0} = {0} + 1", conf.column(f))),
+        )
+        .map(|set| LitStr::new(&set, span))
+        .collect();
+    let keep_existing = LitStr::new(
+        &format!("{0} = {0}", conf.column(&conf.primary_key[0])),
+        span,
+    );
+    let returning = LitStr::new(
+        &format!(" RETURNING {}", conf.runtime_select_columns()),
+        span,
+    );
+
     quote! {
       impl #struct_name {
         #[must_use = "don't forget to save your update"]
         pub fn update(self) -> #update_struct {
-          #update_struct::new(self.state, self.attrs.id)
+          let id = #primary_key_value;
+          #update_struct::new(self.state, id #lock_version_from_model)
         }
       }
 
@@ -1417,11 +3144,13 @@ fn build_update(conf: &SqlxModelConf) -> TokenStream2 {
         pub state: #state_name,
         pub attrs: #update_attrs_struct,
         pub id: #id_type,
+        #lock_version_field
+        #(#auto_now_idents: Option<#auto_now_types>,)*
       }
 
       impl #update_struct {
-        pub fn new(state: #state_name, id: #id_type) -> Self {
-          Self{ state, id, attrs: Default::default() }
+        pub fn new(state: #state_name, id: #id_type #lock_version_param) -> Self {
+          Self{ state, id, #lock_version_init attrs: Default::default(), #(#auto_now_idents: None,)* }
         }
 
         #(
@@ -1431,25 +3160,72 @@ fn build_update(conf: &SqlxModelConf) -> TokenStream2 {
           }
         )*
 
+        #(
+          /// Sets this field instead of leaving it to now().
+          pub fn #auto_now_idents(mut self, val: #auto_now_types) -> Self {
+            self.#auto_now_idents = Some(val);
+            self
+          }
+        )*
+
         pub fn use_struct(mut self, value: #update_attrs_struct) -> Self {
           self.attrs = value;
           self
         }
 
-        pub async fn save(self) -> std::result::Result<#struct_name, sqlx::Error> {
-          let attrs = self.state.db.fetch_one(
-            sqlx::query_as!(
-              #attrs_struct,
-              #query_for_update,
-              self.id,
-              #(#args_for_update),*
-            )
-          ).await?;
+        pub async fn save(self) -> sqlx_models_orm::Result<#struct_name> {
+          let query = sqlx::query_as!(
+            #attrs_struct,
+            #query_for_update,
+            #(#args_for_id,)*
+            #(#args_for_update,)*
+            #lock_version_arg
+          );
+          let attrs = self.state.db.#fetch_updated;
 
           Ok(#struct_name::new(self.state.clone(), attrs))
         }
       }
 
+      impl #select_struct {
+        fn update_all_query(&self, attrs: #update_attrs_struct) -> sqlx_models_orm::SelectQuery {
+          let mut query = sqlx_models_orm::SelectQuery::new(#update_all_start);
+          let mut sets = query.builder.separated(", ");
+          let mut empty = true;
+          #(
+            if let Some(value) = attrs.#field_idents {
+              sets.push(#set_columns).push_bind_unseparated(value);
+              empty = false;
+            }
+          )*
+          #(
+            sets.push(#automatic_sets);
+            empty = false;
+          )*
+          if empty {
+            sets.push(#keep_existing);
+          }
+          self.push_conditions(&mut query);
+          query
+        }
+
+        /// Updates every row matching this select in one statement, returning how many changed.
+        pub async fn update_all(&self, attrs: #update_attrs_struct) -> sqlx_models_orm::Result<u64> {
+          let mut query = self.update_all_query(attrs);
+          let result = self.state.db.execute(query.builder.build()).await #map_err?;
+          Ok(result.rows_affected())
+        }
+
+        /// Like update_all(), but returns the updated rows.
+        pub async fn update_all_returning(&self, attrs: #update_attrs_struct) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let mut query = self.update_all_query(attrs);
+          query.builder.push(#returning);
+          let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+          let attrs = self.state.db.fetch_all(query).await #map_err?;
+          Ok(attrs.into_iter().map(|a| #struct_name::new(self.state.clone(), a)).collect())
+        }
+      }
+
       #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
       pub struct #update_attrs_struct {
         #( pub #field_idents: Option<#field_types>,)*
@@ -1458,18 +3234,151 @@ fn build_update(conf: &SqlxModelConf) -> TokenStream2 {
 }
 
 fn build_delete(conf: &SqlxModelConf) -> TokenStream2 {
+    let map_err = conf.map_err();
     let struct_name = &conf.struct_name;
     let table_name = &conf.table_name;
+    let attrs_struct = &conf.attrs_struct;
     let span = conf.struct_name.span();
 
-    let query_for_delete = LitStr::new(&format!("DELETE FROM {} WHERE id = $1", table_name), span);
+    let primary_key = &conf.primary_key;
+
+    let query_for_delete = LitStr::new(
+        &format!(
+            "DELETE FROM {} WHERE {}",
+            table_name,
+            conf.primary_key_where_clause()
+        ),
+        span,
+    );
+
+    let select_struct = format_ident!("Select{}Hub", &struct_name);
+    let delete_all_start = LitStr::new(&format!("DELETE FROM {}", table_name), span);
+    let returning = LitStr::new(
+        &format!(" RETURNING {}", conf.runtime_select_columns()),
+        span,
+    );
+
+    let soft_delete = match conf.soft_delete.as_ref() {
+        Some(column) => column,
+        None => {
+            return quote! {
+              impl #struct_name {
+                pub async fn delete(self) -> sqlx_models_orm::Result<()> {
+                  self.state.db.execute(sqlx::query!(#query_for_delete, #(self.attrs.#primary_key),*)).await #map_err?;
+                  Ok(())
+                }
+              }
+
+              impl #select_struct {
+                fn delete_all_query(&self) -> sqlx_models_orm::SelectQuery {
+                  let mut query = sqlx_models_orm::SelectQuery::new(#delete_all_start);
+                  self.push_conditions(&mut query);
+                  query
+                }
+
+                /// Deletes every row matching this select in one statement, returning how many were removed.
+                pub async fn delete_all(&self) -> sqlx_models_orm::Result<u64> {
+                  let mut query = self.delete_all_query();
+                  let result = self.state.db.execute(query.builder.build()).await #map_err?;
+                  Ok(result.rows_affected())
+                }
+
+                /// Like delete_all(), but returns the deleted rows.
+                pub async fn delete_all_returning(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+                  let mut query = self.delete_all_query();
+                  query.builder.push(#returning);
+                  let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+                  let attrs = self.state.db.fetch_all(query).await #map_err?;
+                  Ok(attrs.into_iter().map(|a| #struct_name::new(self.state.clone(), a)).collect())
+                }
+              }
+            };
+        }
+    };
+
+    let soft_delete_all_start = LitStr::new(
+        &format!(
+            "UPDATE {} SET {} = now()",
+            table_name,
+            conf.column(soft_delete)
+        ),
+        span,
+    );
+    let not_deleted = LitStr::new(&format!("{} IS NULL", conf.column(soft_delete)), span);
+
+    let query_for_soft_delete = LitStr::new(
+        &format!(
+            "UPDATE {} SET {} = now() WHERE {} AND {}",
+            table_name,
+            conf.column(soft_delete),
+            conf.primary_key_where_clause(),
+            not_deleted.value()
+        ),
+        span,
+    );
+
+    let query_for_restore = LitStr::new(
+        &format!(
+            "UPDATE {} SET {} = NULL WHERE {} RETURNING {}",
+            table_name,
+            conf.column(soft_delete),
+            conf.primary_key_where_clause(),
+            &conf.sql_select_columns,
+        ),
+        span,
+    );
 
     quote! {
       impl #struct_name {
-        pub async fn delete(self) -> sqlx::Result<()> {
-          self.state.db.execute(sqlx::query!(#query_for_delete, self.attrs.id)).await?;
+        pub async fn delete(self) -> sqlx_models_orm::Result<()> {
+          self.state.db.execute(sqlx::query!(#query_for_soft_delete, #(self.attrs.#primary_key),*)).await #map_err?;
+          Ok(())
+        }
+
+        pub async fn hard_delete(self) -> sqlx_models_orm::Result<()> {
+          self.state.db.execute(sqlx::query!(#query_for_delete, #(self.attrs.#primary_key),*)).await #map_err?;
           Ok(())
         }
+
+        pub async fn restore(self) -> sqlx_models_orm::Result<Self> {
+          let attrs = self.state.db.fetch_one(
+            sqlx::query_as!(#attrs_struct, #query_for_restore, #(self.attrs.#primary_key),*)
+          ).await #map_err?;
+          Ok(Self::new(self.state, attrs))
+        }
+      }
+
+      impl #select_struct {
+        fn delete_all_query(&self) -> sqlx_models_orm::SelectQuery {
+          let mut query = sqlx_models_orm::SelectQuery::new(#soft_delete_all_start);
+          self.push_conditions(&mut query);
+          query.condition().push(#not_deleted);
+          query
+        }
+
+        /// Soft deletes every row matching this select in one statement, returning how many were deleted.
+        pub async fn delete_all(&self) -> sqlx_models_orm::Result<u64> {
+          let mut query = self.delete_all_query();
+          let result = self.state.db.execute(query.builder.build()).await #map_err?;
+          Ok(result.rows_affected())
+        }
+
+        /// Like delete_all(), but returns the deleted rows.
+        pub async fn delete_all_returning(&self) -> sqlx_models_orm::Result<Vec<#struct_name>> {
+          let mut query = self.delete_all_query();
+          query.builder.push(#returning);
+          let query = query.builder.build().try_map(|row| <#attrs_struct as sqlx::FromRow<_>>::from_row(&row));
+          let attrs = self.state.db.fetch_all(query).await #map_err?;
+          Ok(attrs.into_iter().map(|a| #struct_name::new(self.state.clone(), a)).collect())
+        }
+
+        /// Removes every row matching this select's conditions for good.
+        pub async fn hard_delete_all(&self) -> sqlx_models_orm::Result<u64> {
+          let mut query = sqlx_models_orm::SelectQuery::new(#delete_all_start);
+          self.push_conditions(&mut query);
+          let result = self.state.db.execute(query.builder.build()).await #map_err?;
+          Ok(result.rows_affected())
+        }
       }
     }
 }
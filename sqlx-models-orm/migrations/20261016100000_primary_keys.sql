@@ -0,0 +1,11 @@
+CREATE TABLE vets (
+  license VARCHAR PRIMARY KEY NOT NULL,
+  name VARCHAR NOT NULL
+);
+
+CREATE TABLE toy_ratings (
+  cat_id VARCHAR NOT NULL,
+  toy_id INTEGER NOT NULL,
+  stars INTEGER NOT NULL,
+  PRIMARY KEY (cat_id, toy_id)
+);
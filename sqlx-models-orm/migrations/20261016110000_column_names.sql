@@ -0,0 +1,6 @@
+CREATE TABLE vaccines (
+  id SERIAL PRIMARY KEY NOT NULL,
+  cat_id VARCHAR NOT NULL,
+  "type" VARCHAR NOT NULL,
+  "doseNumber" INTEGER NOT NULL
+);
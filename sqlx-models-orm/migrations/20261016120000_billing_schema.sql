@@ -0,0 +1,13 @@
+CREATE SCHEMA billing;
+
+CREATE TABLE billing.invoices (
+  id SERIAL PRIMARY KEY NOT NULL,
+  human_id INTEGER NOT NULL,
+  amount INTEGER NOT NULL
+);
+
+CREATE TABLE billing."Invoice Items" (
+  id SERIAL PRIMARY KEY NOT NULL,
+  invoice_id INTEGER NOT NULL,
+  description VARCHAR NOT NULL
+);
@@ -0,0 +1,7 @@
+CREATE TABLE notes (
+  id SERIAL PRIMARY KEY NOT NULL,
+  cat_id VARCHAR NOT NULL,
+  body TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,6 @@
+CREATE TABLE adoption_requests (
+  id SERIAL PRIMARY KEY NOT NULL,
+  cat_id VARCHAR NOT NULL,
+  human_id INTEGER NOT NULL,
+  deleted_at TIMESTAMPTZ
+);
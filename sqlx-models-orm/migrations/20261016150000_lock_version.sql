@@ -0,0 +1,6 @@
+CREATE TABLE feeding_schedules (
+  id SERIAL PRIMARY KEY NOT NULL,
+  cat_id VARCHAR NOT NULL,
+  portions INTEGER NOT NULL,
+  lock_version INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,3 @@
+ALTER TABLE toy_ratings
+  ADD CONSTRAINT toy_ratings_toy_id_fkey FOREIGN KEY (toy_id) REFERENCES toys (id),
+  ADD CONSTRAINT toy_ratings_stars_check CHECK (stars BETWEEN 1 AND 5);
@@ -0,0 +1,5 @@
+CREATE TABLE events (
+  id SERIAL PRIMARY KEY NOT NULL,
+  name VARCHAR NOT NULL,
+  priority INTEGER
+);
@@ -0,0 +1,7 @@
+CREATE TABLE visits (
+  id SERIAL PRIMARY KEY NOT NULL,
+  path VARCHAR NOT NULL,
+  duration INTEGER NOT NULL
+);
+
+CREATE INDEX visits_path ON visits (path);
@@ -0,0 +1,8 @@
+CREATE TABLE settings (
+  id SERIAL PRIMARY KEY NOT NULL,
+  name VARCHAR NOT NULL,
+  value VARCHAR NOT NULL,
+  description TEXT,
+  updated_at TIMESTAMPTZ NOT NULL,
+  CONSTRAINT settings_name_key UNIQUE (name)
+);
@@ -0,0 +1,4 @@
+CREATE TABLE tickets (
+  id SERIAL PRIMARY KEY NOT NULL,
+  issued_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
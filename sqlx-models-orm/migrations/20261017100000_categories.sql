@@ -0,0 +1,5 @@
+CREATE TABLE categories (
+  id SERIAL PRIMARY KEY NOT NULL,
+  name VARCHAR NOT NULL,
+  parent_id INTEGER REFERENCES categories (id)
+);
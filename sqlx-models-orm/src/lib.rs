@@ -1,4 +1,8 @@
+pub use async_stream;
 pub use async_trait::async_trait;
+pub use bytes::Bytes;
+pub use futures_core::stream::BoxStream;
+pub use futures_util;
 pub use sqlx;
 pub use sqlx_models_derive::model;
 use std::ops::DerefMut;
@@ -16,8 +20,8 @@ pub trait SqlxModel: Send + Sync + Sized {
 pub trait SqlxModelHub<Model: SqlxModel>: Send + Sync + Sized {
     fn from_state(state: Model::State) -> Self;
     fn select(&self) -> Model::SelectModelHub;
-    async fn find(&self, id: &Model::Id) -> sqlx::Result<Model>;
-    async fn find_optional(&self, id: &Model::Id) -> sqlx::Result<Option<Model>>;
+    async fn find(&self, id: &Model::Id) -> Result<Model>;
+    async fn find_optional(&self, id: &Model::Id) -> Result<Option<Model>>;
 }
 
 #[async_trait]
@@ -26,16 +30,661 @@ pub trait SqlxSelectModelHub<Model: SqlxModel>: Send + Sync + Sized {
     fn order_by(self, val: Model::ModelOrderBy) -> Self;
     fn maybe_order_by(self, val: Option<Model::ModelOrderBy>) -> Self;
     fn desc(self, val: bool) -> Self;
+    fn order(self, val: Vec<(Model::ModelOrderBy, Direction, Nulls)>) -> Self;
     fn limit(self, val: i64) -> Self;
     fn offset(self, val: i64) -> Self;
+    fn after(self, val: Cursor) -> Self;
+    fn before(self, val: Cursor) -> Self;
     fn use_struct(self, value: Model::SelectModel) -> Self;
-    async fn all(&self) -> sqlx::Result<Vec<Model>>;
-    async fn count(&self) -> sqlx::Result<i64>;
-    async fn one(&self) -> sqlx::Result<Model>;
-    async fn optional(&self) -> sqlx::Result<Option<Model>>;
+    async fn all(&self) -> Result<Vec<Model>>;
+    async fn count(&self) -> Result<i64>;
+    async fn exists(&self) -> Result<bool>;
+    async fn one(&self) -> Result<Model>;
+    async fn optional(&self) -> Result<Option<Model>>;
+    fn stream(&self) -> BoxStream<'static, Result<Model>>;
+    fn in_batches(&self, batch_size: i64) -> BoxStream<'static, Result<Vec<Model>>>;
+    async fn page(&self) -> Result<CursorPage<Model>>;
+    async fn paginate(&self, page: i64, per_page: i64) -> Result<Page<Model>>;
 }
 
-pub use sqlx::{postgres::*, query::*, Error, Postgres, Transaction};
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound {
+        model: &'static str,
+        id: Option<String>,
+    },
+    UniqueViolation {
+        model: &'static str,
+        constraint: Option<String>,
+    },
+    ForeignKeyViolation {
+        model: &'static str,
+        constraint: Option<String>,
+    },
+    CheckViolation {
+        model: &'static str,
+        constraint: Option<String>,
+    },
+    StaleObject {
+        model: &'static str,
+    },
+    InvalidCursor(String),
+    Sqlx(sqlx::Error),
+}
+
+impl Error {
+    /// Classifies an error returned by a query on the given model.
+    pub fn from_sqlx(model: &'static str, error: sqlx::Error) -> Self {
+        use sqlx::error::ErrorKind;
+
+        if let sqlx::Error::RowNotFound = error {
+            return Error::NotFound { model, id: None };
+        }
+
+        let (kind, constraint) = match error.as_database_error() {
+            Some(e) => (e.kind(), e.constraint().map(|c| c.to_string())),
+            None => return Error::Sqlx(error),
+        };
+
+        match kind {
+            ErrorKind::UniqueViolation => Error::UniqueViolation { model, constraint },
+            ErrorKind::ForeignKeyViolation => Error::ForeignKeyViolation { model, constraint },
+            ErrorKind::CheckViolation => Error::CheckViolation { model, constraint },
+            _ => Error::Sqlx(error),
+        }
+    }
+
+    /// Like from_sqlx(), but reports values in the cursor that Postgres can't read
+    /// as the column's type as an invalid cursor.
+    pub fn from_sqlx_with_cursor(
+        model: &'static str,
+        error: sqlx::Error,
+        cursor: Option<&Cursor>,
+    ) -> Self {
+        let code = error.as_database_error().and_then(|e| e.code());
+        match (cursor, code.as_deref()) {
+            (Some(cursor), Some("22P02" | "22003" | "22007" | "22008" | "22021")) => {
+                Error::InvalidCursor(cursor.to_string())
+            }
+            _ => Error::from_sqlx(model, error),
+        }
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        Error::Sqlx(error)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFound {
+                model,
+                id: Some(id),
+            } => write!(f, "{model} {id} not found"),
+            Error::NotFound { model, id: None } => write!(f, "{model} not found"),
+            Error::UniqueViolation { model, constraint } => {
+                write!(f, "{model} violates unique constraint")?;
+                write_constraint(f, constraint)
+            }
+            Error::ForeignKeyViolation { model, constraint } => {
+                write!(f, "{model} violates foreign key constraint")?;
+                write_constraint(f, constraint)
+            }
+            Error::CheckViolation { model, constraint } => {
+                write!(f, "{model} violates check constraint")?;
+                write_constraint(f, constraint)
+            }
+            Error::StaleObject { model } => {
+                write!(f, "{model} was changed by someone else since it was loaded")
+            }
+            Error::InvalidCursor(cursor) => write!(f, "invalid cursor {cursor:?}"),
+            Error::Sqlx(e) => e.fmt(f),
+        }
+    }
+}
+
+fn write_constraint(
+    f: &mut std::fmt::Formatter<'_>,
+    constraint: &Option<String>,
+) -> std::fmt::Result {
+    match constraint {
+        Some(name) => write!(f, " {name}"),
+        None => Ok(()),
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Sqlx(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Points at a row in a keyset paginated select, to fetch the rows after or before it.
+/// It holds the text representation of the sort columns and primary key of that row,
+/// and is encoded as an opaque string to be handed out to API clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub values: Vec<Option<String>>,
+    pub primary_key: Vec<String>,
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string(&(&self.values, &self.primary_key))
+            .map_err(|_| std::fmt::Error)?;
+        f.write_str(&base64::encode_config(json, base64::URL_SAFE_NO_PAD))
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidCursor(s.to_string());
+        let json = base64::decode_config(s, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())?;
+        let (values, primary_key) = serde_json::from_slice(&json).map_err(|_| invalid())?;
+        Ok(Cursor {
+            values,
+            primary_key,
+        })
+    }
+}
+
+impl serde::Serialize for Cursor {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Cursor {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        encoded.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next: Option<Cursor>,
+    pub previous: Option<Cursor>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+/// A column a select is sorted by, as the generated queries see it.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct SortTerm {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub name: &'static str,
+    pub db_type: Option<&'static str>,
+    pub nullable: bool,
+    pub desc: bool,
+    pub nulls_first: bool,
+}
+
+/// Assembles the SQL for selects at runtime, with only the conditions that were
+/// actually set, so Postgres can plan each query with the right indexes.
+#[doc(hidden)]
+pub struct SelectQuery {
+    pub builder: sqlx::QueryBuilder<'static, Postgres>,
+    has_conditions: bool,
+    depth: usize,
+    alias: Option<String>,
+    aliases: usize,
+}
+
+impl SelectQuery {
+    pub fn new(init: impl Into<String>) -> Self {
+        Self {
+            builder: sqlx::QueryBuilder::new(init),
+            has_conditions: false,
+            depth: 0,
+            alias: None,
+            aliases: 0,
+        }
+    }
+
+    /// Starts a new condition in the WHERE clause, returning the builder to push it to.
+    pub fn condition(&mut self) -> &mut sqlx::QueryBuilder<'static, Postgres> {
+        if self.has_conditions {
+            self.builder.push(" AND ");
+        } else if self.depth == 0 {
+            self.builder.push(" WHERE ");
+        }
+        self.has_conditions = true;
+        &mut self.builder
+    }
+
+    /// Starts a condition made of a group of conditions, such as "NOT (".
+    /// Conditions pushed until close_group() are joined with AND.
+    pub fn open_group(&mut self, open: &str) {
+        self.condition().push(open);
+        self.has_conditions = false;
+        self.depth += 1;
+    }
+
+    /// Starts another set of conditions in the current group, after 'separator'.
+    pub fn separate_group(&mut self, separator: &str) {
+        self.end_conjunction();
+        self.builder.push(separator);
+        self.has_conditions = false;
+    }
+
+    pub fn close_group(&mut self, close: &str) {
+        self.end_conjunction();
+        self.builder.push(close);
+        self.has_conditions = true;
+        self.depth -= 1;
+    }
+
+    /// Pushes a query with its own WHERE clause, like the subquery in "IN (SELECT ...)".
+    pub fn subquery<F: FnOnce(&mut Self)>(&mut self, init: &str, push: F) {
+        let (has_conditions, depth, alias) = (self.has_conditions, self.depth, self.alias.take());
+        self.builder.push(init);
+        self.has_conditions = false;
+        self.depth = 0;
+        push(self);
+        self.has_conditions = has_conditions;
+        self.depth = depth;
+        self.alias = alias;
+    }
+
+    /// Like subquery(), but 'table' gets an alias of its own, passed to 'push', so the subquery
+    /// can refer to an outer query on the same table.
+    pub fn aliased_subquery<F: FnOnce(&mut Self, &str)>(
+        &mut self,
+        select: &str,
+        table: &str,
+        push: F,
+    ) {
+        self.aliases += 1;
+        let alias = format!("sqlx_models_exists_{}", self.aliases);
+        self.subquery(&format!("{select} FROM {table} AS {alias}"), |q| {
+            q.alias = Some(alias.clone());
+            push(q, &alias);
+        });
+    }
+
+    /// How conditions being pushed refer to the table they filter, its alias if it has one.
+    pub fn qualifier<'a>(&'a self, table: &'a str) -> &'a str {
+        self.alias.as_deref().unwrap_or(table)
+    }
+
+    // An empty set of conditions matches every row.
+    fn end_conjunction(&mut self) {
+        if !self.has_conditions {
+            self.builder.push("true");
+        }
+    }
+
+    /// Keeps only the rows that come after the given values in the order of 'terms'.
+    pub fn after(&mut self, terms: &[SortTerm], values: &[Option<String>]) {
+        let builder = self.condition();
+        builder.push("(");
+        for k in 0..terms.len() {
+            if k > 0 {
+                builder.push(" OR ");
+            }
+            builder.push("(");
+            for j in 0..k {
+                Self::push_tie(builder, &terms[j], &values[j]);
+                builder.push(" AND ");
+            }
+            Self::push_after(builder, &terms[k], &values[k]);
+            builder.push(")");
+        }
+        builder.push(")");
+    }
+
+    fn push_value(
+        builder: &mut sqlx::QueryBuilder<'static, Postgres>,
+        term: &SortTerm,
+        value: &str,
+    ) {
+        match term.db_type {
+            Some(db_type) => {
+                builder
+                    .push("CAST(")
+                    .push_bind(value.to_string())
+                    .push(format!(" AS {db_type})"));
+            }
+            // Without a db_type the value is read with the column's own type,
+            // through a record of the table's row type.
+            None => {
+                builder
+                    .push(format!(
+                        "(jsonb_populate_record(NULL::{}, jsonb_build_object(",
+                        term.table
+                    ))
+                    .push_bind(term.name)
+                    .push("::text, ")
+                    .push_bind(value.to_string())
+                    .push(format!("::text))).{}", term.column));
+            }
+        }
+    }
+
+    fn push_column(builder: &mut sqlx::QueryBuilder<'static, Postgres>, term: &SortTerm) {
+        builder.push(term.column);
+    }
+
+    fn push_tie(
+        builder: &mut sqlx::QueryBuilder<'static, Postgres>,
+        term: &SortTerm,
+        value: &Option<String>,
+    ) {
+        Self::push_column(builder, term);
+        match value {
+            Some(v) => {
+                builder.push(" = ");
+                Self::push_value(builder, term, v);
+            }
+            None => {
+                builder.push(" IS NULL");
+            }
+        }
+    }
+
+    fn push_after(
+        builder: &mut sqlx::QueryBuilder<'static, Postgres>,
+        term: &SortTerm,
+        value: &Option<String>,
+    ) {
+        match value {
+            Some(v) => {
+                builder.push("(");
+                Self::push_column(builder, term);
+                builder.push(if term.desc { " < " } else { " > " });
+                Self::push_value(builder, term, v);
+                if term.nullable && !term.nulls_first {
+                    builder.push(format!(" OR {} IS NULL", term.column));
+                }
+                builder.push(")");
+            }
+            None if term.nulls_first => {
+                builder.push(format!("{} IS NOT NULL", term.column));
+            }
+            None => {
+                builder.push("false");
+            }
+        }
+    }
+
+    pub fn order_by(&mut self, terms: &[SortTerm]) {
+        for (i, term) in terms.iter().enumerate() {
+            self.builder.push(if i == 0 { " ORDER BY " } else { ", " });
+            self.builder.push(term.column);
+            self.builder.push(if term.desc { " DESC" } else { " ASC" });
+            if term.nullable {
+                self.builder.push(if term.nulls_first {
+                    " NULLS FIRST"
+                } else {
+                    " NULLS LAST"
+                });
+            }
+        }
+    }
+
+    pub fn limit_offset(&mut self, limit: Option<i64>, offset: Option<i64>) {
+        if let Some(limit) = limit {
+            self.builder.push(" LIMIT ").push_bind(limit);
+        }
+        if let Some(offset) = offset {
+            self.builder.push(" OFFSET ").push_bind(offset);
+        }
+    }
+
+    /// Extra columns with the text representation of the sort terms, to build cursors.
+    pub fn cursor_columns(terms: &[SortTerm]) -> String {
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!(r#", {}::text AS "cursor_{}""#, t.column, i))
+            .collect()
+    }
+
+    pub fn cursor_values(row: &PgRow, terms: usize) -> sqlx::Result<Vec<Option<String>>> {
+        use sqlx::Row;
+        (0..terms)
+            .map(|i| row.try_get(format!("cursor_{i}").as_str()))
+            .collect()
+    }
+
+    /// Streams the rows of the select made by 'build' as CSV with a header, using COPY.
+    /// COPY takes no arguments, so selects that need them are first saved to a temporary table.
+    pub fn copy_out<F>(db: Db, build: F) -> BoxStream<'static, Result<Bytes>>
+    where
+        F: Fn(&str) -> Result<SelectQuery> + Send + Sync + 'static,
+    {
+        use futures_util::TryStreamExt;
+
+        Box::pin(async_stream::try_stream! {
+            use sqlx::{Arguments, Execute};
+
+            let mut query = build("COPY (")?;
+            query.builder.push(") TO STDOUT WITH (FORMAT csv, HEADER)");
+            let mut built = query.builder.build();
+            let arguments = built.take_arguments().map_err(sqlx::Error::Encode)?;
+            if arguments.map(|a| a.len() > 0).unwrap_or(false) {
+                let tx = if db.in_transaction().await {
+                    db.clone()
+                } else {
+                    db.transaction().await?
+                };
+                let table = temp_table_name("copy_out");
+                let mut staged = build(&format!("CREATE TEMP TABLE {table} ON COMMIT DROP AS "))?;
+                tx.execute(staged.builder.build()).await?;
+                {
+                    let statement = format!("COPY {table} TO STDOUT WITH (FORMAT csv, HEADER)");
+                    let mut chunks = tx.copy_out(&statement);
+                    while let Some(chunk) = chunks.try_next().await? {
+                        yield chunk;
+                    }
+                }
+                tx.execute(sqlx::query(&format!("DROP TABLE {table}"))).await?;
+            } else {
+                let statement = built.sql().to_string();
+                let mut chunks = db.copy_out(&statement);
+                while let Some(chunk) = chunks.try_next().await? {
+                    yield chunk;
+                }
+            }
+        })
+    }
+}
+
+/// A name for a temporary table that no other statement in this process is using,
+/// so several of them can live in the same transaction.
+#[doc(hidden)]
+pub fn temp_table_name(purpose: &str) -> String {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    format!("sqlx_models_{purpose}_{n}")
+}
+
+/// Rows for `COPY ... FROM STDIN WITH (FORMAT binary)`, encoded like query arguments.
+#[doc(hidden)]
+pub struct BinaryCopyIn {
+    buffer: PgArgumentBuffer,
+}
+
+impl BinaryCopyIn {
+    const SIGNATURE: &'static [u8] = b"PGCOPY\n\xff\r\n\0";
+    const CHUNK_SIZE: usize = 1 << 20;
+
+    pub fn new() -> Self {
+        let mut buffer = PgArgumentBuffer::default();
+        buffer.extend_from_slice(Self::SIGNATURE);
+        buffer.extend_from_slice(&0i32.to_be_bytes()); // flags
+        buffer.extend_from_slice(&0i32.to_be_bytes()); // header extension length
+        Self { buffer }
+    }
+
+    pub fn row(&mut self, fields: i16) {
+        self.buffer.extend_from_slice(&fields.to_be_bytes());
+    }
+
+    /// Types only known to sqlx by name, like enums, and JSON get their encoding fixed up
+    /// once Postgres tells their type for a query, which COPY doesn't do, so they are rejected.
+    pub fn field<'q, T>(&mut self, value: &T) -> sqlx::Result<()>
+    where
+        T: sqlx::Encode<'q, Postgres> + sqlx::Type<Postgres>,
+    {
+        use sqlx::TypeInfo;
+
+        // json, json[], jsonb and jsonb[]
+        const JSON_OIDS: [u32; 4] = [114, 199, 3802, 3807];
+
+        let ty = value.produces().unwrap_or_else(T::type_info);
+        match ty.oid() {
+            Some(oid) if !JSON_OIDS.contains(&oid.0) => {}
+            _ => {
+                return Err(sqlx::Error::Encode(
+                    format!(
+                        "{} values can't be sent with COPY, use insert_many() instead",
+                        ty.name()
+                    )
+                    .into(),
+                ))
+            }
+        }
+
+        let offset = self.buffer.len();
+        self.buffer.extend_from_slice(&[0; 4]);
+        let len = match value.encode_by_ref(&mut self.buffer) {
+            Ok(sqlx::encode::IsNull::Yes) => -1,
+            Ok(sqlx::encode::IsNull::No) => (self.buffer.len() - offset - 4) as i32,
+            Err(e) => return Err(sqlx::Error::Encode(e)),
+        };
+        self.buffer[offset..offset + 4].copy_from_slice(&len.to_be_bytes());
+        Ok(())
+    }
+
+    /// Encodes 'items' lazily, in chunks of about a megabyte.
+    pub fn chunks<T, I, F>(items: I, mut encode: F) -> impl Iterator<Item = sqlx::Result<Vec<u8>>>
+    where
+        I: IntoIterator<Item = T>,
+        F: FnMut(&mut Self, T) -> sqlx::Result<()>,
+    {
+        let mut items = items.into_iter();
+        let mut copy = Some(Self::new());
+        std::iter::from_fn(move || {
+            let mut current = copy.take()?;
+            for item in items.by_ref() {
+                if let Err(e) = encode(&mut current, item) {
+                    return Some(Err(e));
+                }
+                if current.buffer.len() >= Self::CHUNK_SIZE {
+                    let chunk = std::mem::take(&mut *current.buffer);
+                    copy = Some(current);
+                    return Some(Ok(chunk));
+                }
+            }
+            current.buffer.extend_from_slice(&(-1i16).to_be_bytes()); // trailer
+            Some(Ok(std::mem::take(&mut *current.buffer)))
+        })
+    }
+}
+
+impl Default for BinaryCopyIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn send_copy_in<C, I>(mut copy: PgCopyIn<C>, chunks: I) -> sqlx::Result<u64>
+where
+    C: DerefMut<Target = PgConnection>,
+    I: IntoIterator<Item = sqlx::Result<Vec<u8>>>,
+{
+    for chunk in chunks {
+        match chunk {
+            Ok(data) => {
+                copy.send(data).await?;
+            }
+            Err(e) => {
+                copy.abort(e.to_string()).await?;
+                return Err(e);
+            }
+        }
+    }
+    copy.finish().await
+}
+
+/// Reads a foreign key field, which may be optional, to match it to a primary key
+/// when preloading associations.
+#[doc(hidden)]
+pub trait ForeignKey<T> {
+    fn key(&self) -> Option<&T>;
+}
+
+impl<T> ForeignKey<T> for T {
+    fn key(&self) -> Option<&T> {
+        Some(self)
+    }
+}
+
+impl<T> ForeignKey<T> for Option<T> {
+    fn key(&self) -> Option<&T> {
+        self.as_ref()
+    }
+}
+
+/// One page of a select paginated with limit and offset. Pages are numbered from 1.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub total_pages: i64,
+    pub has_next: bool,
+}
+
+impl<T> Page<T> {
+    /// Pages and page sizes below 1 are taken as 1.
+    pub fn new(items: Vec<T>, page: i64, per_page: i64, total: i64) -> Self {
+        let page = page.max(1);
+        let per_page = per_page.max(1);
+        let total_pages = (total + per_page - 1) / per_page;
+        Page {
+            items,
+            page,
+            per_page,
+            total,
+            total_pages,
+            has_next: page < total_pages,
+        }
+    }
+}
+
+pub use sqlx::{postgres::*, query::*, Postgres, Transaction};
 
 pub type PgTx =
     Option<std::sync::Arc<futures_util::lock::Mutex<Option<Transaction<'static, Postgres>>>>>;
@@ -65,7 +714,7 @@ macro_rules! define_query_method {
     ($method:ident, $return:ty) => {
         pub async fn $method<'a, T, F>(&self, query: PgMap<'a, F>) -> sqlx::Result<$return>
         where
-            F: FnMut(sqlx::postgres::PgRow) -> Result<T, Error> + Send,
+            F: FnMut(sqlx::postgres::PgRow) -> std::result::Result<T, sqlx::Error> + Send,
             T: Unpin + Send,
         {
             choose_executor!(self, query, $method)
@@ -104,6 +753,14 @@ impl Db {
         })
     }
 
+    /// Whether queries on this Db run in a transaction that hasn't been committed yet.
+    pub async fn in_transaction(&self) -> bool {
+        match self.transaction.as_ref() {
+            Some(arc) => arc.lock().await.is_some(),
+            None => false,
+        }
+    }
+
     pub async fn execute<'a>(&self, query: PgQuery<'a>) -> sqlx::Result<PgQueryResult> {
         choose_executor!(self, query, execute)
     }
@@ -112,6 +769,68 @@ impl Db {
     define_query_method! {fetch_all, Vec<T>}
     define_query_method! {fetch_optional, Option<T>}
 
+    /// Streams the rows of a query instead of collecting them.
+    /// Inside a transaction it holds the connection until the stream is dropped,
+    /// so awaiting another query on this Db while the stream is alive never finishes.
+    pub fn fetch<'a, T, F>(&'a self, query: PgMap<'a, F>) -> BoxStream<'a, sqlx::Result<T>>
+    where
+        F: FnMut(sqlx::postgres::PgRow) -> std::result::Result<T, sqlx::Error> + Send + 'a,
+        T: Unpin + Send + 'a,
+    {
+        use futures_util::TryStreamExt;
+
+        Box::pin(async_stream::try_stream! {
+            let mut guard = match self.transaction.as_ref() {
+                Some(arc) => Some(arc.lock().await),
+                None => None,
+            };
+            let mut rows = match guard.as_deref_mut().and_then(|tx| tx.as_mut()) {
+                Some(tx) => query.fetch(tx.deref_mut()),
+                None => query.fetch(&self.pool),
+            };
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        })
+    }
+
+    /// Runs a `COPY ... FROM STDIN` statement, sending it each chunk of data.
+    /// Returns the number of rows copied.
+    pub async fn copy_in<I>(&self, statement: &str, chunks: I) -> sqlx::Result<u64>
+    where
+        I: IntoIterator<Item = sqlx::Result<Vec<u8>>>,
+        I::IntoIter: Send,
+    {
+        if let Some(arc) = self.transaction.as_ref() {
+            let mut mutex = arc.lock().await;
+            if let Some(tx) = &mut *mutex {
+                return send_copy_in(tx.copy_in_raw(statement).await?, chunks).await;
+            }
+        }
+        send_copy_in(self.pool.copy_in_raw(statement).await?, chunks).await
+    }
+
+    /// Streams the output of a `COPY ... TO STDOUT` statement.
+    /// Like fetch(), it holds the transaction until the stream is dropped,
+    /// and awaiting other queries on this Db meanwhile never finishes.
+    pub fn copy_out<'a>(&'a self, statement: &'a str) -> BoxStream<'a, sqlx::Result<Bytes>> {
+        use futures_util::TryStreamExt;
+
+        Box::pin(async_stream::try_stream! {
+            let mut guard = match self.transaction.as_ref() {
+                Some(arc) => Some(arc.lock().await),
+                None => None,
+            };
+            let mut chunks = match guard.as_deref_mut().and_then(|tx| tx.as_mut()) {
+                Some(tx) => tx.copy_out_raw(statement).await?,
+                None => self.pool.copy_out_raw(statement).await?,
+            };
+            while let Some(chunk) = chunks.try_next().await? {
+                yield chunk;
+            }
+        })
+    }
+
     define_query_scalar_method! {fetch_one_scalar, fetch_one, T}
     define_query_scalar_method! {fetch_all_scalar, fetch_all, Vec<T>}
     define_query_scalar_method! {fetch_optional_scalar, fetch_optional, Option<T>}